@@ -1,4 +1,61 @@
+use std::collections::HashMap;
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
-    tonic_build::compile_protos("proto/flow.proto")?;
+    let proto = std::fs::read_to_string("proto/flow.proto")?;
+    // the server is only used by `mock::MockAccessApi`
+    let mut builder = tonic_build::configure()
+        .build_server(std::env::var_os("CARGO_FEATURE_MOCK").is_some())
+        .type_attribute(
+            ".",
+            "#[cfg_attr(feature = \"serde-types\", derive(serde::Serialize, serde::Deserialize))]",
+        );
+    // bytes fields are serialized as hex strings
+    for (path, codec) in bytes_fields(&proto) {
+        builder = builder.field_attribute(
+            path,
+            format!(
+                "#[cfg_attr(feature = \"serde-types\", serde(with = \"{}\"))]",
+                codec
+            ),
+        );
+    }
+    builder.compile(&["proto/flow.proto"], &["proto"])?;
+    println!("cargo:rerun-if-changed=proto/flow.proto");
     Ok(())
 }
+
+/// Finds every `bytes` field in the proto, returning its fully qualified path and the serde codec it needs
+fn bytes_fields(proto: &str) -> HashMap<String, &'static str> {
+    let mut fields = HashMap::new();
+    let mut scope: Vec<String> = vec![];
+    let mut package = String::new();
+    for line in proto.lines() {
+        let line = line.split("//").next().unwrap_or("").trim();
+        let tokens: Vec<&str> = line.split_whitespace().collect();
+        match tokens.as_slice() {
+            ["package", name] => package = name.trim_end_matches(';').to_owned(),
+            ["message", _, "{}"] => {}
+            ["message", name, ..] | ["enum", name, ..] | ["service", name, ..] => {
+                scope.push(name.trim_end_matches('{').to_owned())
+            }
+            ["}"] => {
+                scope.pop();
+            }
+            ["bytes", name, ..] => {
+                fields.insert(field_path(&package, &scope, name), "crate::hex_serde");
+            }
+            ["repeated", "bytes", name, ..] => {
+                fields.insert(field_path(&package, &scope, name), "crate::hex_serde::vec");
+            }
+            ["map<string,", "bytes>", name, ..] => {
+                fields.insert(field_path(&package, &scope, name), "crate::hex_serde::map");
+            }
+            _ => {}
+        }
+    }
+    fields
+}
+
+fn field_path(package: &str, scope: &[String], name: &str) -> String {
+    format!(".{}.{}.{}", package, scope.join("."), name)
+}
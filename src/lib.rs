@@ -9,6 +9,7 @@
 // ****************************************************
 // External Dependencies
 // ****************************************************
+#[cfg(feature = "transport")]
 use flow::access_api_client::AccessApiClient;
 
 use flow::*;
@@ -17,820 +18,6144 @@ pub mod flow {
     //! `flow` is an exported module from the flow_rust_sdk.
     //! It's types are generated directly from the gRPC API Protobufs
     //! https://github.com/onflow/flow/tree/master/protobuf
+    //!
+    //! With the `serde-types` feature enabled they also implement `Serialize`/`Deserialize`, with bytes encoded as hex.
     tonic::include_proto!("flow.access");
 }
 
-// for signing transactions
-use bytes::Bytes;
+/// Interprets the raw `status` of a `TransactionResultResponse`; unrecognised values are `Unknown`.
+/// `TransactionResultResponse::status()` returns the typed status directly.
+impl From<i32> for flow::TransactionStatus {
+    fn from(status: i32) -> Self {
+        flow::TransactionStatus::from_i32(status).unwrap_or(flow::TransactionStatus::Unknown)
+    }
+}
+
+/// Convenience accessors for indexers, with ids hex-encoded
+impl flow::Block {
+    /// The ids of the collections guaranteed in this block
+    pub fn collection_ids(&self) -> Vec<String> {
+        self.collection_guarantees
+            .iter()
+            .map(|guarantee| hex::encode(&guarantee.collection_id))
+            .collect()
+    }
+    /// The ids of the blocks sealed by this block
+    pub fn sealed_block_ids(&self) -> Vec<String> {
+        self.block_seals
+            .iter()
+            .map(|seal| hex::encode(&seal.block_id))
+            .collect()
+    }
+    /// The seal of the block with the hex-encoded id, if this block seals it
+    pub fn seal_for(&self, block_id: &str) -> Option<&flow::BlockSeal> {
+        let block_id = decode_block_id(block_id).ok()?;
+        self.block_seals
+            .iter()
+            .find(|seal| seal.block_id == block_id)
+    }
+    /// The aggregated signatures of the consensus nodes that proposed and voted for this block
+    pub fn signatures(&self) -> Vec<String> {
+        self.signatures.iter().map(hex::encode).collect()
+    }
+}
+
+/// Accessors for verifying a seal, with ids and signatures hex-encoded
+impl flow::BlockSeal {
+    /// The id of the execution receipt the seal commits to
+    pub fn execution_receipt_id(&self) -> String {
+        hex::encode(&self.execution_receipt_id)
+    }
+    /// The signatures of the execution nodes over the execution receipt
+    pub fn execution_receipt_signatures(&self) -> Vec<String> {
+        self.execution_receipt_signatures
+            .iter()
+            .map(hex::encode)
+            .collect()
+    }
+    /// The signatures of the verification nodes that approved the execution result
+    pub fn result_approval_signatures(&self) -> Vec<String> {
+        self.result_approval_signatures
+            .iter()
+            .map(hex::encode)
+            .collect()
+    }
+}
+
+/// Accessors for attributing a collection to the cluster that guaranteed it, with ids and signatures hex-encoded.
+/// `signer_indices` is only set by nodes since the signer indices upgrade; older nodes list the signers in `signer_ids`.
+impl flow::CollectionGuarantee {
+    /// The id of the guaranteed collection
+    pub fn collection_id(&self) -> String {
+        hex::encode(&self.collection_id)
+    }
+    /// The aggregated signature of the collection nodes that guaranteed the collection
+    pub fn signature(&self) -> String {
+        hex::encode(&self.signature)
+    }
+    /// The node ids of the collection nodes that signed, from nodes predating signer indices
+    pub fn signer_ids(&self) -> Vec<String> {
+        self.signer_ids.iter().map(hex::encode).collect()
+    }
+    /// The signer indices: a 4 byte checksum of the cluster's committee, then a bit vector of which members signed
+    pub fn signer_indices(&self) -> String {
+        hex::encode(&self.signer_indices)
+    }
+    /// The positions in the cluster's committee of the collection nodes that signed, from the signer indices
+    pub fn signer_positions(&self) -> Vec<usize> {
+        let bits = self.signer_indices.get(4..).unwrap_or(&[]);
+        (0..bits.len() * 8)
+            .filter(|position| bits[position / 8] & (0x80 >> (position % 8)) != 0)
+            .collect()
+    }
+}
+
+/// Conversions to wall-clock time, eg. for bucketing blocks and events by time
+impl flow::Timestamp {
+    /// The timestamp as a `SystemTime`
+    pub fn to_system_time(&self) -> std::time::SystemTime {
+        let nanos = std::time::Duration::from_nanos(self.nanos.max(0) as u64);
+        let seconds = std::time::Duration::from_secs(self.seconds.unsigned_abs());
+        if self.seconds >= 0 {
+            std::time::UNIX_EPOCH + seconds + nanos
+        } else {
+            // the nanos still count forward from the negative seconds
+            std::time::UNIX_EPOCH - seconds + nanos
+        }
+    }
+    /// The timestamp as a UTC `DateTime`, or `None` if it is out of `chrono`'s range
+    #[cfg(feature = "chrono")]
+    pub fn to_date_time(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        use chrono::TimeZone;
+        chrono::Utc
+            .timestamp_opt(self.seconds, self.nanos.max(0) as u32)
+            .single()
+    }
+}
+
+impl From<std::time::SystemTime> for flow::Timestamp {
+    fn from(time: std::time::SystemTime) -> Self {
+        match time.duration_since(std::time::UNIX_EPOCH) {
+            Ok(since) => flow::Timestamp {
+                seconds: since.as_secs() as i64,
+                nanos: since.subsec_nanos() as i32,
+            },
+            Err(e) => {
+                // before the epoch: round the seconds down, so the nanos count forward
+                let before = e.duration();
+                let nanos = before.subsec_nanos();
+                flow::Timestamp {
+                    seconds: -(before.as_secs() as i64) - (nanos > 0) as i64,
+                    nanos: if nanos > 0 {
+                        1_000_000_000 - nanos as i32
+                    } else {
+                        0
+                    },
+                }
+            }
+        }
+    }
+}
+
+/// Typed views of a transaction result, eg. for displaying it
+impl flow::TransactionResultResponse {
+    /// The computation used by the transaction, which is 0 from nodes that do not report it
+    pub fn computation_used(&self) -> u64 {
+        self.computation_usage
+    }
+    /// The events emitted by the transaction, with their payloads decoded
+    pub fn decoded_events(&self) -> Result<Vec<DecodedEvent>> {
+        self.events.iter().map(DecodedEvent::decode).collect()
+    }
+}
+
+/// Accessors which error, rather than panic, when the account does not exist
+impl flow::AccountResponse {
+    /// A reference to the account, if it exists
+    pub fn account(&self) -> Result<&flow::Account> {
+        match &self.account {
+            Some(account) => Ok(account),
+            None => bail!(FlowError::AccountNotFound),
+        }
+    }
+    /// The account, if it exists
+    pub fn into_account(self) -> Result<flow::Account> {
+        match self.account {
+            Some(account) => Ok(account),
+            None => bail!(FlowError::AccountNotFound),
+        }
+    }
+    /// The FLOW balance of the account, in the smallest unit (1e-8 FLOW)
+    pub fn account_balance(&self) -> Result<u64> {
+        match &self.account {
+            Some(account) => Ok(account.balance),
+            None => bail!(FlowError::AccountNotFound),
+        }
+    }
+    /// The names of the contracts deployed to the account, sorted
+    pub fn contract_names(&self) -> Result<Vec<String>> {
+        match &self.account {
+            Some(account) => {
+                let mut names: Vec<String> = account.contracts.keys().cloned().collect();
+                names.sort();
+                Ok(names)
+            }
+            None => bail!(FlowError::AccountNotFound),
+        }
+    }
+    /// The code of the named contract deployed to the account
+    pub fn contract(&self, name: &str) -> Result<&[u8]> {
+        match &self.account {
+            Some(account) => match account.contracts.get(name) {
+                Some(code) => Ok(code),
+                None => bail!("Contract {} not found", name),
+            },
+            None => bail!(FlowError::AccountNotFound),
+        }
+    }
+}
+
+/// Serializes the generated types' bytes fields as hex strings
+#[cfg(feature = "serde-types")]
+mod hex_serde {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        hex::decode(String::deserialize(deserializer)?).map_err(serde::de::Error::custom)
+    }
+
+    /// For `repeated bytes` fields
+    pub mod vec {
+        use serde::{Deserialize, Deserializer, Serializer};
+
+        pub fn serialize<S: Serializer>(items: &[Vec<u8>], serializer: S) -> Result<S::Ok, S::Error> {
+            serializer.collect_seq(items.iter().map(hex::encode))
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<Vec<Vec<u8>>, D::Error> {
+            Vec::<String>::deserialize(deserializer)?
+                .into_iter()
+                .map(|item| hex::decode(item).map_err(serde::de::Error::custom))
+                .collect()
+        }
+    }
+
+    /// For `map<string, bytes>` fields
+    pub mod map {
+        use serde::{Deserialize, Deserializer, Serializer};
+        use std::collections::HashMap;
+
+        pub fn serialize<S: Serializer>(
+            map: &HashMap<String, Vec<u8>>,
+            serializer: S,
+        ) -> Result<S::Ok, S::Error> {
+            serializer.collect_map(map.iter().map(|(key, value)| (key, hex::encode(value))))
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(
+            deserializer: D,
+        ) -> Result<HashMap<String, Vec<u8>>, D::Error> {
+            HashMap::<String, String>::deserialize(deserializer)?
+                .into_iter()
+                .map(|(key, value)| {
+                    hex::decode(value)
+                        .map(|value| (key, value))
+                        .map_err(serde::de::Error::custom)
+                })
+                .collect()
+        }
+    }
+}
+
+#[cfg(not(any(feature = "p256-flow", feature = "p256-upstream")))]
+compile_error!("enable a signing backend: the `p256-flow` (default) or `p256-upstream` feature");
+
+// for signing transactions, from the selected backend
+#[cfg(not(feature = "p256-upstream"))]
 pub use p256_flow::ecdsa::SigningKey;
-use p256_flow::ecdsa::{signature_flow::Signature, signature_flow::Signer};
+#[cfg(feature = "p256-upstream")]
+pub use p256_upstream::ecdsa::SigningKey;
+#[cfg(not(feature = "p256-upstream"))]
+use p256_flow::ecdsa::{
+    signature_flow::Signature, signature_flow::Signer, signature_flow::Verifier, VerifyingKey,
+};
+#[cfg(not(feature = "p256-upstream"))]
 use p256_flow::elliptic_curve_flow::SecretKey;
 pub use rand_core::OsRng;
+#[cfg(feature = "transport")]
+use rand_core::RngCore;
 pub extern crate hex;
 pub extern crate rlp;
 use rlp::*;
-use tonic::transport::Channel;
+use sha3::{Digest, Sha3_256};
+#[cfg(feature = "transport")]
+use tonic::metadata::{Ascii, MetadataKey, MetadataMap, MetadataValue};
+#[cfg(feature = "transport")]
+use tonic::transport::{Channel, Endpoint};
+#[cfg(feature = "transport")]
+use tonic::codegen::{Body, StdError};
+#[cfg(feature = "transport")]
+use tonic::{body::BoxBody, client::GrpcService};
 use anyhow::{Result, bail};
+use std::borrow::Cow;
+#[cfg(feature = "transport")]
 use http::uri::Uri;
+#[cfg(feature = "transport")]
+use std::future::Future;
+#[cfg(feature = "transport")]
+use std::sync::Arc;
+#[cfg(feature = "transport")]
+use tokio::sync::Semaphore;
+#[cfg(feature = "transport")]
+pub use tokio_stream::Stream;
+// ****************************************************
+// Errors
+// ****************************************************
+
+/// Errors produced by the SDK itself, returned inside `anyhow::Error`.
+/// Use `error.downcast_ref::<FlowError>()` to match on them.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FlowError {
+    /// The transaction was sealed, but failed during execution
+    CadenceRuntime { message: String, status_code: u32 },
+    /// The transaction was rejected because its reference block is too old; rebuild it with a recent block
+    ReferenceBlockExpired(String),
+    /// The transaction was rejected because the node has already seen it
+    DuplicateTransaction(String),
+    /// The transaction was rejected because one of its signatures is invalid
+    InvalidSignature(String),
+    /// The request did not complete within the connection's timeout
+    Timeout(String),
+    /// `send_transaction` was given no transaction to send
+    UnsignedTransaction,
+    /// The requested account does not exist
+    AccountNotFound,
+}
+
+impl std::fmt::Display for FlowError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FlowError::CadenceRuntime {
+                message,
+                status_code,
+            } => write!(f, "Error during execution (status code {}): {}", status_code, message),
+            FlowError::ReferenceBlockExpired(message) => {
+                write!(f, "Reference block expired: {}", message)
+            }
+            FlowError::DuplicateTransaction(message) => {
+                write!(f, "Duplicate transaction: {}", message)
+            }
+            FlowError::InvalidSignature(message) => write!(f, "Invalid signature: {}", message),
+            FlowError::Timeout(message) => write!(f, "Timed out: {}", message),
+            FlowError::UnsignedTransaction => {
+                write!(f, "No transaction to send; build and sign it first")
+            }
+            FlowError::AccountNotFound => write!(f, "Account not found"),
+        }
+    }
+}
+
+impl std::error::Error for FlowError {}
+
+#[cfg(feature = "transport")]
+/// Adds a known rejection as a `FlowError`, so callers can eg. rebuild an expired transaction.
+/// The `tonic::Status` can still be downcast to.
+fn with_rejection(error: anyhow::Error) -> anyhow::Error {
+    let rejection = error.downcast_ref::<tonic::Status>().and_then(rejection);
+    match rejection {
+        Some(rejection) => error.context(rejection),
+        None => error,
+    }
+}
+
+#[cfg(feature = "transport")]
+/// Recognises the reasons a node gives for rejecting a transaction.
+/// Only the codes a node rejects a transaction with are considered, so transport failures, whose outcome is unknown, are never mistaken for one.
+fn rejection(status: &tonic::Status) -> Option<FlowError> {
+    match status.code() {
+        tonic::Code::InvalidArgument
+        | tonic::Code::FailedPrecondition
+        | tonic::Code::AlreadyExists => {}
+        _ => return None,
+    }
+    let message = status.message().to_owned();
+    let lowercase = message.to_lowercase();
+    if lowercase.contains("expired") {
+        Some(FlowError::ReferenceBlockExpired(message))
+    } else if lowercase.contains("duplicate") || lowercase.contains("already") {
+        Some(FlowError::DuplicateTransaction(message))
+    } else if lowercase.contains("signature") {
+        Some(FlowError::InvalidSignature(message))
+    } else {
+        None
+    }
+}
+
 // ****************************************************
 // Connection Object
 // ****************************************************
 
+#[cfg(feature = "transport")]
+/// The default number of times a request may rebuild its channel after a transport error
+pub const DEFAULT_MAX_RECONNECTS: u32 = 3;
+
+/// The number of blocks after its reference block that a transaction remains valid for
+pub const TRANSACTION_EXPIRY: u64 = 600;
+
+/// The gas limit of the transactions sent by the `FlowConnection` helpers, unless set with `with_gas_limit`
+pub const DEFAULT_GAS_LIMIT: u64 = 1000;
+
+/// The highest gas limit the network accepts
+pub const MAX_GAS_LIMIT: u64 = 9999;
+
+#[cfg(feature = "transport")]
+/// The most requests a batch helper, such as `get_accounts`, has in flight at once
+pub const MAX_CONCURRENT_REQUESTS: usize = 32;
+
+#[cfg(feature = "transport")]
+/// Whether the status was produced by the transport (eg. a dropped connection) rather than by the node
+fn is_transport_error(status: &tonic::Status) -> bool {
+    match status.code() {
+        tonic::Code::Unavailable => true,
+        tonic::Code::Unknown => status.message().starts_with("transport error"),
+        _ => false,
+    }
+}
+
+#[cfg(feature = "transport")]
+/// Converts the status of a failed request, returning a deadline exceeded as `FlowError::Timeout`
+fn status_error(status: tonic::Status) -> anyhow::Error {
+    match status.code() {
+        tonic::Code::DeadlineExceeded => FlowError::Timeout(status.message().to_owned()).into(),
+        // tonic servers cancel a request whose `grpc-timeout` passed, which can reach the client before its own deadline
+        tonic::Code::Cancelled if status.message() == "Timeout expired" => {
+            FlowError::Timeout(status.message().to_owned()).into()
+        }
+        _ => status.into(),
+    }
+}
+
+#[cfg(feature = "transport")]
+/// Names a request and its key inputs, so its errors say which of several calls failed
+trait Describe {
+    fn describe(&self) -> String;
+    /// The error a `NotFound` status means for this request, if more specific than the status
+    fn not_found(&self) -> Option<FlowError> {
+        None
+    }
+}
+
+#[cfg(feature = "transport")]
+impl Describe for GetLatestBlockRequest {
+    fn describe(&self) -> String {
+        format!("latest block (sealed: {})", self.is_sealed)
+    }
+}
+
+#[cfg(feature = "transport")]
+impl Describe for GetBlockByIdRequest {
+    fn describe(&self) -> String {
+        format!("block {}", hex::encode(&self.id))
+    }
+}
+
+#[cfg(feature = "transport")]
+impl Describe for GetBlockByHeightRequest {
+    fn describe(&self) -> String {
+        format!("block at height {}", self.height)
+    }
+}
+
+#[cfg(feature = "transport")]
+impl Describe for GetCollectionByIdRequest {
+    fn describe(&self) -> String {
+        format!("collection {}", hex::encode(&self.id))
+    }
+}
+
+#[cfg(feature = "transport")]
+impl Describe for SendTransactionRequest {
+    fn describe(&self) -> String {
+        "send transaction".to_owned()
+    }
+}
+
+#[cfg(feature = "transport")]
+impl Describe for GetTransactionRequest {
+    fn describe(&self) -> String {
+        format!("transaction {}", hex::encode(&self.id))
+    }
+}
+
+#[cfg(feature = "transport")]
+impl Describe for GetAccountAtLatestBlockRequest {
+    fn describe(&self) -> String {
+        format!("account 0x{}", hex::encode(&self.address))
+    }
+    fn not_found(&self) -> Option<FlowError> {
+        Some(FlowError::AccountNotFound)
+    }
+}
+
+#[cfg(feature = "transport")]
+impl Describe for ExecuteScriptAtLatestBlockRequest {
+    fn describe(&self) -> String {
+        "script at the latest block".to_owned()
+    }
+}
+
+#[cfg(feature = "transport")]
+impl Describe for ExecuteScriptAtBlockIdRequest {
+    fn describe(&self) -> String {
+        format!("script at block {}", hex::encode(&self.block_id))
+    }
+}
+
+#[cfg(feature = "transport")]
+impl Describe for ExecuteScriptAtBlockHeightRequest {
+    fn describe(&self) -> String {
+        format!("script at height {}", self.block_height)
+    }
+}
+
+#[cfg(feature = "transport")]
+impl Describe for GetEventsForHeightRangeRequest {
+    fn describe(&self) -> String {
+        format!(
+            "{} events in heights {}..={}",
+            self.r#type, self.start_height, self.end_height
+        )
+    }
+}
+
+#[cfg(feature = "transport")]
+impl Describe for GetNodeVersionInfoRequest {
+    fn describe(&self) -> String {
+        "node version info".to_owned()
+    }
+}
+
+#[cfg(feature = "transport")]
+impl Describe for GetEventsForBlockIdsRequest {
+    fn describe(&self) -> String {
+        format!("{} events in {} blocks", self.r#type, self.block_ids.len())
+    }
+}
+
+#[cfg(feature = "transport")]
+/// Converts the status of a failed request like `status_error`, with the request and status code as context,
+/// eg. "account 0x0000000000000001 failed: NotFound". The status, and any `FlowError` from `Describe::not_found`, can still be downcast to.
+fn request_error<M: Describe>(message: &M, status: tonic::Status) -> anyhow::Error {
+    let context = format!("{} failed: {:?}", message.describe(), status.code());
+    let not_found = match status.code() {
+        tonic::Code::NotFound => message.not_found(),
+        _ => None,
+    };
+    match not_found {
+        Some(not_found) => status_error(status).context(not_found).context(context),
+        None => status_error(status).context(context),
+    }
+}
+
+#[cfg(feature = "transport")]
+/// Whether the status is worth retrying, ie. the node is temporarily unavailable or overloaded
+pub fn is_transient_error(status: &tonic::Status) -> bool {
+    matches!(
+        status.code(),
+        tonic::Code::Unavailable | tonic::Code::ResourceExhausted
+    )
+}
+
+#[cfg(feature = "transport")]
+/// An opt-in retry policy for read-only requests that fail with a transient error.
+/// `send_transaction` is never retried, since that risks a double submission.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    /// The total number of attempts, including the first
+    pub max_attempts: u32,
+    /// The backoff before the first retry, doubled for each following retry
+    pub initial_backoff: Duration,
+    /// The upper bound of the backoff
+    pub max_backoff: Duration,
+    /// Decides whether a failed request should be retried
+    pub retry_on: fn(&tonic::Status) -> bool,
+}
+
+#[cfg(feature = "transport")]
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+            retry_on: is_transient_error,
+        }
+    }
+}
+
+#[cfg(feature = "transport")]
+impl RetryPolicy {
+    /// Exponential backoff with jitter for the given retry (starting at 1)
+    fn backoff(&self, retry: u32) -> Duration {
+        let exponential = self.initial_backoff.as_millis() << (retry - 1).min(16);
+        let capped = exponential.min(self.max_backoff.as_millis()) as u64;
+        // keep half of the backoff, and randomize the other half
+        let jitter = OsRng.next_u64() % (capped / 2 + 1);
+        Duration::from_millis(capped / 2 + jitter)
+    }
+}
+
+#[cfg(feature = "transport")]
+/// How often, and for how long, to poll for a transaction result while waiting for it to be sealed
+#[derive(Clone, Debug)]
+pub struct PollConfig {
+    /// The wait between polls
+    pub interval: Duration,
+    /// How many times to poll before giving up
+    pub max_attempts: u32,
+}
+
+#[cfg(feature = "transport")]
+impl Default for PollConfig {
+    fn default() -> Self {
+        PollConfig {
+            interval: Duration::from_millis(500),
+            max_attempts: 120,
+        }
+    }
+}
+
+#[cfg(feature = "transport")]
+/// Hands out increasing sequence numbers for proposal keys locally, so transactions sent in quick succession
+/// from the same key do not all use the on-chain sequence number, which only advances once each is included.
+/// Clones share their state. Attach it with `FlowConnection::with_sequence_tracker`.
+#[derive(Clone, Debug, Default)]
+pub struct SequenceTracker {
+    next: Arc<std::sync::Mutex<NextSequenceNumbers>>,
+}
+#[cfg(feature = "transport")]
+/// The next sequence number of each tracked proposal key, by address and key id
+type NextSequenceNumbers = std::collections::HashMap<(Vec<u8>, u32), u64>;
+#[cfg(feature = "transport")]
+impl SequenceTracker {
+    /// A tracker which does not know any keys yet
+    pub fn new() -> Self {
+        SequenceTracker::default()
+    }
+    /// The sequence number for the next transaction proposed by the key, fetching the on-chain one the first time
+    pub async fn next<T>(
+        &self,
+        connection: &mut FlowConnection<T>,
+        address: &str,
+        key_id: u32,
+    ) -> Result<u64>
+    where
+        T: GrpcService<BoxBody> + Clone + Send + Sync + 'static,
+        T::Future: Send,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body + Send + Sync + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        let key = (decode_address(address)?, key_id);
+        if let Some(sequence_number) = self.next.lock().unwrap().get_mut(&key) {
+            *sequence_number += 1;
+            return Ok(*sequence_number - 1);
+        }
+        let account = connection.get_account(address).await?.into_account()?;
+        let on_chain = key_sequence_number(&account, key_id)?;
+        // another task may have started tracking the key while the account was fetched
+        let mut next = self.next.lock().unwrap();
+        let sequence_number = next.entry(key).or_insert(on_chain);
+        *sequence_number += 1;
+        Ok(*sequence_number - 1)
+    }
+    /// Forgets the key, so its next sequence number is fetched from the chain again.
+    /// Call this when a transaction proposed by the key was not included, eg. it was rejected or never sent.
+    pub fn reset(&self, address: &str, key_id: u32) {
+        // a malformed address cannot have been tracked
+        if let Ok(address) = decode_address(address) {
+            self.next.lock().unwrap().remove(&(address, key_id));
+        }
+    }
+    /// Forgets the key after the node rejected a transaction it proposed, if the tracker handed out its sequence number
+    fn rejected(&self, key: &TransactionProposalKey) {
+        let mut next = self.next.lock().unwrap();
+        let tracked = (key.address.clone(), key.key_id);
+        if matches!(next.get(&tracked), Some(next) if key.sequence_number < *next) {
+            next.remove(&tracked);
+        }
+    }
+}
+
+#[cfg(feature = "transport")]
+/// A script together with its arguments and the block to run it at, eg.
+/// `ScriptQuery::new().source(script).arg(Argument::uint64(1)).execute(&mut connection)`.
+/// Runs against the latest block unless `at_block` or `at_height` is set.
+#[derive(Clone, Debug, Default)]
+pub struct ScriptQuery {
+    script: Vec<u8>,
+    arguments: Vec<Vec<u8>>,
+    block_height: Option<u64>,
+    block_id: Option<String>,
+}
+#[cfg(feature = "transport")]
+impl ScriptQuery {
+    /// An empty script, to be given its source with `source`
+    pub fn new() -> Self {
+        ScriptQuery::default()
+    }
+    /// The Cadence source of the script
+    pub fn source(mut self, script: impl Into<Vec<u8>>) -> Self {
+        self.script = script.into();
+        self
+    }
+    /// Appends an argument to the script's `main`, in order
+    pub fn arg<A: IntoCadenceArg>(mut self, argument: A) -> Self {
+        self.arguments.push(argument.into_cadence_arg());
+        self
+    }
+    /// Run the script against the hex-encoded block id, rather than the latest block
+    pub fn at_block(mut self, block_id: &str) -> Self {
+        self.block_id = Some(block_id.to_owned());
+        self.block_height = None;
+        self
+    }
+    /// Run the script against the block at the height, rather than the latest block
+    pub fn at_height(mut self, block_height: u64) -> Self {
+        self.block_height = Some(block_height);
+        self.block_id = None;
+        self
+    }
+    /// Runs the script with `FlowConnection::execute_script`. Errors without contacting the node if there is no source.
+    pub async fn execute<T>(
+        &self,
+        connection: &mut FlowConnection<T>,
+    ) -> Result<ExecuteScriptResponse>
+    where
+        T: GrpcService<BoxBody> + Clone + Send + Sync + 'static,
+        T::Future: Send,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body + Send + Sync + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        if self.script.is_empty() {
+            bail!("The script has no source");
+        }
+        connection
+            .execute_script(
+                self.script.clone(),
+                self.arguments.clone(),
+                self.block_height,
+                self.block_id.clone(),
+            )
+            .await
+    }
+    /// Runs the script like `execute`, then decodes the JSON-Cadence result into `R`
+    pub async fn execute_typed<R: DeserializeOwned, T>(
+        &self,
+        connection: &mut FlowConnection<T>,
+    ) -> Result<R>
+    where
+        T: GrpcService<BoxBody> + Clone + Send + Sync + 'static,
+        T::Future: Send,
+        T::Error: Into<StdError>,
+        T::ResponseBody: Body + Send + Sync + 'static,
+        <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+    {
+        let response = self.execute(connection).await?;
+        CadenceValue::decode(&response.value)?.deserialize()
+    }
+}
+
+#[cfg(feature = "transport")]
+/// The widest height range an Access node serves in a single `GetEventsForHeightRange` request
+pub const MAX_EVENT_HEIGHT_RANGE: u64 = 250;
+
+#[cfg(feature = "transport")]
+/// The position of an incremental event reader: the last sealed height whose events have been returned.
+/// Persist it to resume with `poll_events_since` after a restart.
+/// Start it with `EventCursor::at`, as nodes only serve heights since their spork's root block.
+#[cfg_attr(feature = "serde-types", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EventCursor {
+    pub last_height: u64,
+}
+#[cfg(feature = "transport")]
+impl EventCursor {
+    /// A cursor whose first poll returns events from `start_height` on, eg. the latest sealed height to read only new events,
+    /// or `spork_root_block_height` from `get_node_version_info` to read the whole spork
+    pub fn at(start_height: u64) -> Self {
+        EventCursor {
+            last_height: start_height.saturating_sub(1),
+        }
+    }
+}
+
+#[cfg(feature = "transport")]
 /// The FlowConnection object contains a single API connection.
-/// The network transport layer can be optionally substitued by implementing a new FlowConnection<T>
+/// The network transport layer can be substituted with any `tonic` `GrpcService`, eg. a channel wrapped in an
+/// `InterceptedService` for authentication; see `from_service`.
+///
+/// The `tonic` version used here does not limit the size of decoded or encoded messages, so large blocks and script results are accepted as-is.
+/// A "message larger than max" error comes from the Access node's own limits, and is not configurable from the client.
+///
+/// Cloning is cheap and every clone shares the underlying channel, so to use one connection from several tasks,
+/// give each task its own clone rather than sharing it behind a `Mutex`.
 #[derive(Clone, Debug)]
 pub struct FlowConnection<T> {
     pub client: AccessApiClient<T>,
+    /// Metadata (headers) attached to every request
+    metadata: MetadataMap,
+    /// The endpoint the channel was built from, used to rebuild it after a transport error.
+    /// `None` when the channel or service was provided by the caller.
+    endpoint: Option<Endpoint>,
+    /// Builds a fresh service from the endpoint
+    rebuild: Option<fn(&Endpoint) -> Result<T>>,
+    /// How many times a single request may rebuild the channel before failing
+    max_reconnects: u32,
+    /// How read-only requests are retried, if at all
+    retry_policy: Option<RetryPolicy>,
+    /// How long each RPC may take, if bounded
+    timeout: Option<Duration>,
+    /// Whether requests and responses are gzip-compressed
+    compression: bool,
+    /// The gas limit of the transactions sent by the helpers
+    gas_limit: u64,
+    /// The domain tag the helpers sign transactions with
+    domain_tag: [u8; 32],
+    /// Hands out the helpers' proposal key sequence numbers, if set
+    sequence_tracker: Option<SequenceTracker>,
+    /// The chain the node belongs to, which decides the addresses of the core contracts
+    network: Network,
+    /// The Cadence version the network runs, which decides how the helpers encode keys
+    cadence_version: CadenceVersion,
+    /// The Cadence transactions sent by the account, key, contract and transfer helpers
+    templates: Templates,
+}
+
+#[cfg(feature = "transport")]
+impl<T: Clone> FlowConnection<T> {
+    /// A clone of the underlying client, sharing this connection's channel, for calling the Access API directly from another task.
+    /// Requests made through it do not carry the connection's headers or timeout, and are not retried.
+    pub fn client(&self) -> AccessApiClient<T> {
+        self.client.clone()
+    }
 }
 
+#[cfg(feature = "transport")]
 /// The default implementation of a FlowConnection, using `tonic::transport::Channel`
 impl FlowConnection<tonic::transport::Channel> {
-    /// Initializes a new connection and checks the availability of the node at the provided address
+    /// Initializes a new connection to the provided address.
+    /// The connection is established lazily, so an unreachable node is only reported by the first request; see `connect`.
     pub fn new(
         network_address: &str,
     ) -> Result<FlowConnection<tonic::transport::Channel>> {
         let uri = network_address.parse::<Uri>().unwrap();
         let endpoint = Channel::builder(uri);
         let channel = endpoint.connect_lazy()?;
-        let client = AccessApiClient::new(channel);
-        Ok(FlowConnection::<tonic::transport::Channel> { client })
+        Ok(FlowConnection::from_endpoint(endpoint, channel))
     }
-    /// get_account will return the `flow::AccountResponse` of `account_address`, else an error if it could not be accessed.
-    pub async fn get_account(
-        &mut self,
-        account_address: &str,
-    ) -> Result<AccountResponse> {
-        let request = tonic::Request::new(GetAccountAtLatestBlockRequest {
-            address: hex::decode(account_address).unwrap(),
-        });
-        let response = self.client.get_account_at_latest_block(request).await?;
-        Ok(response.into_inner())
+    /// Connects to the node at the provided address, returning an error immediately if it cannot be reached
+    pub async fn connect(
+        network_address: &str,
+    ) -> Result<FlowConnection<tonic::transport::Channel>> {
+        let uri = network_address.parse::<Uri>()?;
+        let endpoint = Channel::builder(uri);
+        let channel = endpoint.connect().await?;
+        Ok(FlowConnection::from_endpoint(endpoint, channel))
     }
-    /// execute_script will attempt to run the provided script (as bytes) and return the `flow::ExecuteScriptResponse` or Error
-    pub async fn execute_script(
-        &mut self,
-        script: Vec<u8>,
-        arguments: Vec<Vec<u8>>,
-        block_height: Option<u64>,
-        block_id: Option<Vec<u8>>,
-    ) -> Result<ExecuteScriptResponse> {
-        if block_id.is_some() {
-            // we are running the script against a specific block
-            let request = tonic::Request::new(ExecuteScriptAtBlockIdRequest {
-                script,
-                arguments,
-                block_id: block_id.unwrap(),
-            });
-            let response = self.client.execute_script_at_block_id(request).await?;
-            Ok(response.into_inner())
-        } else if block_height.is_some() {
-            // we are running the script against a block height
-            let request = tonic::Request::new(ExecuteScriptAtBlockHeightRequest {
-                script,
-                arguments,
-                block_height: block_height.unwrap(),
-            });
-            let response = self.client.execute_script_at_block_height(request).await?;
-            Ok(response.into_inner())
-        } else {
-            let request =
-                tonic::Request::new(ExecuteScriptAtLatestBlockRequest { script, arguments });
-            let response = self.client.execute_script_at_latest_block(request).await?;
-            Ok(response.into_inner())
+    /// Initializes a connection to a flow-emulator running locally on its default port, `EMULATOR_ACCESS_API`.
+    /// Like `new`, the connection is established lazily. Pair it with `Sign::emulator_service_account()`.
+    /// Current flow-emulator releases run Cadence 1.0, so the connection uses `CadenceVersion::Cadence1`;
+    /// call `with_cadence_version(CadenceVersion::Legacy)` for a pre-1.0 emulator.
+    pub fn emulator() -> Result<FlowConnection<tonic::transport::Channel>> {
+        Ok(FlowConnection::new(EMULATOR_ACCESS_API)?
+            .with_network(Network::Emulator)
+            .with_cadence_version(CadenceVersion::Cadence1))
+    }
+    /// Wraps a channel built by the caller, eg. one with its own middleware or load balancing.
+    /// The SDK cannot rebuild such a channel, so after a transport error it is reused as-is.
+    pub fn from_channel(channel: Channel) -> FlowConnection<tonic::transport::Channel> {
+        FlowConnection::from_service(channel)
+    }
+    fn from_endpoint(endpoint: Endpoint, channel: Channel) -> Self {
+        FlowConnection {
+            endpoint: Some(endpoint),
+            rebuild: Some(|endpoint| Ok(endpoint.connect_lazy()?)),
+            ..FlowConnection::from_channel(channel)
         }
     }
-    /// Sends the transaction to the blockchain.
-    /// Make sure you signed the transactionsign_transaction first.
-    pub async fn send_transaction(
-        &mut self,
-        transaction: Option<Transaction>,
-    ) -> Result<SendTransactionResponse> {
-        // send to blockchain
-        let request = tonic::Request::new(SendTransactionRequest { transaction });
-        let response = self.client.send_transaction(request).await?;
-        Ok(response.into_inner())
+    /// Sets the initial HTTP/2 stream window size, in bytes, eg. raised for bulk event or block backfills.
+    /// The channel is rebuilt with the new setting. Defaults to hyper's, and errors for a connection made with `from_channel`.
+    pub fn with_initial_stream_window_size(self, size: u32) -> Result<Self> {
+        self.with_endpoint(|endpoint| endpoint.initial_stream_window_size(size))
     }
-    /// get transaction result
-    pub async fn get_transaction_result(
-        &mut self,
-        id: Vec<u8>,
-    ) -> Result<TransactionResultResponse> {
-        // send to blockchain
-        let request = tonic::Request::new(GetTransactionRequest { id });
-        let response = self.client.get_transaction_result(request).await?;
-        Ok(response.into_inner())
+    /// Sets the initial HTTP/2 connection window size, in bytes, shared by every stream on the channel.
+    /// The channel is rebuilt with the new setting. Defaults to hyper's, and errors for a connection made with `from_channel`.
+    pub fn with_initial_connection_window_size(self, size: u32) -> Result<Self> {
+        self.with_endpoint(|endpoint| endpoint.initial_connection_window_size(size))
     }
-    /// get_block accepts either the block_id or block_height. If neither are defined it returns the latest block.
-    pub async fn get_block(
-        &mut self,
-        block_id: Option<String>,
-        block_height: Option<u64>,
-        is_sealed: Option<bool>,
-    ) -> Result<BlockResponse> {
-        if block_id.is_some() {
-            // IF block_id, use this
-            let request = tonic::Request::new(GetBlockByIdRequest {
-                id: hex::decode(block_id.unwrap())?,
-            });
-            let response = self.client.get_block_by_id(request).await?;
-            Ok(response.into_inner())
-        } else if block_height.is_some() {
-            // else IF block_height, use that
-            let request = tonic::Request::new(GetBlockByHeightRequest {
-                height: block_height.unwrap(),
-            });
-            let response = self.client.get_block_by_height(request).await?;
-            Ok(response.into_inner())
+    /// Reconfigures the endpoint, then rebuilds the channel from it
+    fn with_endpoint(mut self, configure: impl FnOnce(Endpoint) -> Endpoint) -> Result<Self> {
+        let endpoint = match self.endpoint.take() {
+            Some(endpoint) => endpoint,
+            None => bail!("The channel was provided by the caller, so configure it before calling from_channel"),
+        };
+        self.endpoint = Some(configure(endpoint));
+        self.reconnect()?;
+        Ok(self)
+    }
+}
+
+#[cfg(feature = "transport")]
+/// The API of a FlowConnection, over any `tonic` transport
+impl<T> FlowConnection<T>
+where
+    T: GrpcService<BoxBody> + Clone + Send + Sync + 'static,
+    T::Future: Send,
+    T::Error: Into<StdError>,
+    T::ResponseBody: Body + Send + Sync + 'static,
+    <T::ResponseBody as Body>::Error: Into<StdError> + Send,
+{
+    /// Wraps a service built by the caller, eg. a channel wrapped in an `InterceptedService` that adds authentication.
+    /// Like `from_channel`, the service is reused as-is after a transport error.
+    pub fn from_service(service: T) -> Self {
+        FlowConnection {
+            client: AccessApiClient::new(service),
+            metadata: MetadataMap::new(),
+            endpoint: None,
+            rebuild: None,
+            max_reconnects: DEFAULT_MAX_RECONNECTS,
+            retry_policy: None,
+            timeout: None,
+            compression: false,
+            gas_limit: DEFAULT_GAS_LIMIT,
+            domain_tag: TRANSACTION_DOMAIN_TAG,
+            sequence_tracker: None,
+            network: Network::Emulator,
+            cadence_version: CadenceVersion::Legacy,
+            templates: CadenceVersion::Legacy.templates(),
+        }
+    }
+    /// Adds a header that is sent with every request, eg. the API key required by a hosted Access API provider.
+    pub fn with_header(mut self, key: &str, value: &str) -> Result<Self> {
+        let key = MetadataKey::from_bytes(key.as_bytes())?;
+        let value = value.parse::<MetadataValue<Ascii>>()?;
+        self.metadata.insert(key, value);
+        Ok(self)
+    }
+    /// Sets how many times a single request may rebuild the channel after a transport error before failing.
+    /// Defaults to `DEFAULT_MAX_RECONNECTS`; set to 0 to disable reconnecting.
+    pub fn with_max_reconnects(mut self, max_reconnects: u32) -> Self {
+        self.max_reconnects = max_reconnects;
+        self
+    }
+    /// Sets the chain the node belongs to, so the helpers import the core contracts from the right addresses.
+    /// Defaults to `Network::Emulator`.
+    pub fn with_network(mut self, network: Network) -> Self {
+        self.network = network;
+        self
+    }
+    /// Selects the template set matching the Cadence version the network runs.
+    /// Defaults to `CadenceVersion::Legacy`, or `CadenceVersion::Cadence1` for `emulator`.
+    pub fn with_cadence_version(mut self, version: CadenceVersion) -> Self {
+        self.cadence_version = version;
+        self.templates = version.templates();
+        self
+    }
+    /// Replaces the Cadence transactions sent by the helpers, eg. to add a field or target an unreleased Cadence version.
+    /// Each template must take the same arguments as the built-in one it replaces for the connection's `CadenceVersion`,
+    /// so call this after `with_cadence_version`.
+    pub fn with_templates(mut self, templates: Templates) -> Self {
+        self.templates = templates;
+        self
+    }
+    /// Retries read-only requests which fail with a transient error, according to the policy.
+    /// Transactions are never resent.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = Some(retry_policy);
+        self
+    }
+    /// Bounds each RPC, failing with `FlowError::Timeout` once it elapses. The deadline is also sent to the node.
+    /// Each retry gets its own deadline. Defaults to no timeout.
+    /// To override it for a single call, use a clone, eg. `connection.clone().with_timeout(Some(timeout)).execute_script(..)`.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+    /// Gzip-compresses requests, and asks the node to compress its responses, which cuts the bandwidth of
+    /// large blocks, script results and event ranges at the cost of some CPU. The node must support gzip.
+    #[cfg(feature = "compression")]
+    pub fn with_compression(mut self) -> Self {
+        self.compression = true;
+        self.client = self.client.send_gzip().accept_gzip();
+        self
+    }
+    /// Sets the gas limit of the transactions sent by the helpers, eg. `MAX_GAS_LIMIT` for deploying a large contract,
+    /// which can fail with "computation limit exceeded" at the default.
+    /// Defaults to `DEFAULT_GAS_LIMIT`.
+    pub fn with_gas_limit(mut self, gas_limit: u64) -> Self {
+        self.gas_limit = gas_limit;
+        self
+    }
+    /// Takes the sequence numbers of the helpers' proposal keys from the tracker, rather than the chain, when none is given.
+    /// A transaction the node rejects resets its proposal key in the tracker; after a transport error the key is left alone,
+    /// as the transaction may have been accepted.
+    pub fn with_sequence_tracker(mut self, tracker: SequenceTracker) -> Self {
+        self.sequence_tracker = Some(tracker);
+        self
+    }
+    /// Sets the domain tag the helpers sign transactions with, for Flow-derived chains which use their own.
+    /// Defaults to `TRANSACTION_DOMAIN_TAG`.
+    pub fn with_domain_tag(mut self, domain_tag: [u8; 32]) -> Self {
+        self.domain_tag = domain_tag;
+        self
+    }
+    /// Wraps the message in a `tonic::Request`, attaching the connection's default metadata and timeout
+    fn request<M>(&self, message: M) -> tonic::Request<M> {
+        let mut request = tonic::Request::new(message);
+        *request.metadata_mut() = self.metadata.clone();
+        if let Some(timeout) = self.timeout {
+            request.set_timeout(timeout);
+        }
+        request
+    }
+    /// Replaces the channel with a fresh one built from the original endpoint, if there is one
+    fn reconnect(&mut self) -> Result<()> {
+        if let (Some(endpoint), Some(rebuild)) = (&self.endpoint, self.rebuild) {
+            self.client = AccessApiClient::new(rebuild(endpoint)?);
+            if self.compression {
+                self.client = self.client.clone().send_gzip().accept_gzip();
+            }
+        }
+        Ok(())
+    }
+    /// Performs a single RPC. When the transport fails the channel is rebuilt, with backoff, up to `max_reconnects` times.
+    /// `idempotent` requests are then resent, and retried according to the retry policy.
+    /// Other requests are never sent twice: the channel is still rebuilt for the next call, but the error is returned.
+    async fn call<M, R, F, Fut>(&mut self, message: M, idempotent: bool, rpc: F) -> Result<R>
+    where
+        M: Clone + Describe,
+        F: Fn(AccessApiClient<T>, tonic::Request<M>) -> Fut,
+        Fut: Future<Output = std::result::Result<tonic::Response<R>, tonic::Status>>,
+    {
+        let mut reconnects: u32 = 0;
+        let mut attempts: u32 = 1;
+        loop {
+            #[cfg(feature = "tracing")]
+            let started = std::time::Instant::now();
+            let response = rpc(self.client.clone(), self.request(message.clone()));
+            // the node enforces the deadline too, but a stalled connection would never report it
+            let result = match self.timeout {
+                Some(timeout) => match tokio::time::timeout(timeout, response).await {
+                    Ok(result) => result,
+                    Err(_) => Err(tonic::Status::deadline_exceeded(format!(
+                        "no response after {:?}",
+                        timeout
+                    ))),
+                },
+                None => response.await,
+            };
+            #[cfg(feature = "tracing")]
+            {
+                let method = std::any::type_name::<M>()
+                    .rsplit("::")
+                    .next()
+                    .unwrap_or_default();
+                let elapsed_ms = started.elapsed().as_millis() as u64;
+                match &result {
+                    Ok(_) => tracing::debug!(method, elapsed_ms, "rpc succeeded"),
+                    Err(status) => {
+                        tracing::debug!(method, elapsed_ms, code = ?status.code(), message = status.message(), "rpc failed")
+                    }
+                }
+            }
+            let status = match result {
+                Ok(response) => return Ok(response.into_inner()),
+                Err(status) => status,
+            };
+            if is_transport_error(&status) && reconnects < self.max_reconnects {
+                reconnects += 1;
+                sleep(Duration::from_millis(100 << reconnects.min(6))).await;
+                self.reconnect()?;
+                if idempotent {
+                    continue;
+                }
+                return Err(request_error(&message, status));
+            }
+            match &self.retry_policy {
+                Some(policy)
+                    if idempotent && attempts < policy.max_attempts && (policy.retry_on)(&status) =>
+                {
+                    sleep(policy.backoff(attempts)).await;
+                    attempts += 1;
+                }
+                _ => return Err(request_error(&message, status)),
+            }
+        }
+    }
+    /// Runs `rpc` for each item, on clones of this connection, with at most `MAX_CONCURRENT_REQUESTS` in flight.
+    /// The results are returned in the order of `items`.
+    async fn call_concurrently<I, R, F, Fut>(&self, items: Vec<I>, rpc: F) -> Vec<Result<R>>
+    where
+        F: Fn(Self, I) -> Fut,
+        Fut: Future<Output = Result<R>> + Send + 'static,
+        R: Send + 'static,
+    {
+        let permits = Arc::new(Semaphore::new(MAX_CONCURRENT_REQUESTS));
+        let handles: Vec<_> = items
+            .into_iter()
+            .map(|item| {
+                let permits = permits.clone();
+                let request = rpc(self.clone(), item);
+                tokio::spawn(async move {
+                    let _permit = permits.acquire_owned().await?;
+                    request.await
+                })
+            })
+            .collect();
+        let mut results = Vec::with_capacity(handles.len());
+        for handle in handles {
+            results.push(match handle.await {
+                Ok(result) => result,
+                Err(e) => Err(e.into()),
+            });
+        }
+        results
+    }
+    /// get_account will return the `flow::AccountResponse` of `account_address`, else an error if it could not be accessed.
+    /// A missing account fails with `FlowError::AccountNotFound`, so the response's `account` is always set.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(address = account_address)))]
+    pub async fn get_account(
+        &mut self,
+        account_address: &str,
+    ) -> Result<AccountResponse> {
+        let request = GetAccountAtLatestBlockRequest {
+            address: decode_address(account_address)?,
+        };
+        let context = format!("{} failed", request.describe());
+        let response: AccountResponse = self
+            .call(request, true, |mut client, request| async move {
+                client.get_account_at_latest_block(request).await
+            })
+            .await?;
+        if response.account.is_none() {
+            return Err(anyhow::Error::new(FlowError::AccountNotFound).context(context));
+        }
+        Ok(response)
+    }
+    /// Fetches each account concurrently, up to `MAX_CONCURRENT_REQUESTS` at a time, over the shared channel.
+    /// The results are in the order of `addresses`, so one missing account does not fail the rest.
+    pub async fn get_accounts(&mut self, addresses: &[&str]) -> Vec<Result<AccountResponse>> {
+        let addresses: Vec<String> = addresses
+            .iter()
+            .map(|address| address.to_string())
+            .collect();
+        self.call_concurrently(addresses, |mut connection, address| async move {
+            connection.get_account(&address).await
+        })
+        .await
+    }
+    /// Fetches the account and returns the source of its contract `name`, eg. to verify what is deployed.
+    /// Errors if the account or contract does not exist, or the source is not UTF-8.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(skip_all, fields(address = address, name = name))
+    )]
+    pub async fn get_contract_source(&mut self, address: &str, name: &str) -> Result<String> {
+        let account = self.get_account(address).await?;
+        match account.contract(name) {
+            Ok(code) => Ok(String::from_utf8(code.to_vec())?),
+            Err(_) => bail!("Contract {} not found on account {}", name, address),
+        }
+    }
+    /// check_signing_weight sums the weights of the given keys on the account, erroring if any key is missing or revoked.
+    /// A transaction is only accepted when each signing account reaches a total weight of 1000.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(address = address)))]
+    pub async fn check_signing_weight(&mut self, address: &str, key_ids: &[u32]) -> Result<u64> {
+        let account = self.get_account(address).await?.into_account()?;
+        let keys = parse_account_keys(&account);
+        let mut total: u64 = 0;
+        for key_id in key_ids {
+            match keys.iter().find(|key| key.index == *key_id) {
+                Some(key) if key.revoked => bail!("Key {} on account {} is revoked", key_id, address),
+                Some(key) => total += key.weight as u64,
+                None => bail!("Key {} not found on account {}", key_id, address),
+            }
+        }
+        Ok(total)
+    }
+    /// execute_script will attempt to run the provided script (as bytes) and return the `flow::ExecuteScriptResponse` or Error
+    /// The script runs against the hex-encoded `block_id` if given, else `block_height`, else the latest block.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn execute_script(
+        &mut self,
+        script: Vec<u8>,
+        arguments: Vec<Vec<u8>>,
+        block_height: Option<u64>,
+        block_id: Option<String>,
+    ) -> Result<ExecuteScriptResponse> {
+        if let Some(block_id) = block_id {
+            // we are running the script against a specific block
+            let block_id = decode_block_id(&block_id)?;
+            let request = ExecuteScriptAtBlockIdRequest {
+                script,
+                arguments,
+                block_id,
+            };
+            self.call(request, true, |mut client, request| async move {
+                client.execute_script_at_block_id(request).await
+            })
+            .await
+        } else if let Some(block_height) = block_height {
+            // we are running the script against a block height
+            let request = ExecuteScriptAtBlockHeightRequest {
+                script,
+                arguments,
+                block_height,
+            };
+            self.call(request, true, |mut client, request| async move {
+                client.execute_script_at_block_height(request).await
+            })
+            .await
+        } else {
+            let request = ExecuteScriptAtLatestBlockRequest { script, arguments };
+            self.call(request, true, |mut client, request| async move {
+                client.execute_script_at_latest_block(request).await
+            })
+            .await
+        }
+    }
+    /// execute_script_with_args runs the script like `execute_script`, encoding the arguments for you.
+    /// Arguments of different types can be mixed by passing `Vec<Value>`, eg. `vec![json!(Argument::str("a")), json!(Argument::uint64(1))]`
+    pub async fn execute_script_with_args<A: IntoCadenceArg>(
+        &mut self,
+        script: Vec<u8>,
+        arguments: Vec<A>,
+        block_height: Option<u64>,
+        block_id: Option<String>,
+    ) -> Result<ExecuteScriptResponse> {
+        self.execute_script(script, encode_arguments(arguments), block_height, block_id)
+            .await
+    }
+    /// execute_script_typed runs the script like `execute_script`, then decodes the JSON-Cadence result into `R`.
+    /// eg. a script returning `UFix64` can be decoded into `f64`, and one returning `[Address]` into `Vec<String>`.
+    pub async fn execute_script_typed<R: DeserializeOwned>(
+        &mut self,
+        script: Vec<u8>,
+        arguments: Vec<Vec<u8>>,
+        block_height: Option<u64>,
+        block_id: Option<String>,
+    ) -> Result<R> {
+        let response = self
+            .execute_script(script, arguments, block_height, block_id)
+            .await?;
+        CadenceValue::decode(&response.value)?.deserialize()
+    }
+    /// Simulates a transaction against the latest block, so Cadence reverts can be caught before signing and paying fees.
+    /// Flow has no dry run, so `script` is a script mirroring the transaction's logic: its `main` takes each of the
+    /// `authorizers` as an `Address` before `arguments`, eg. `pub fun main(signer: Address, amount: UFix64)`,
+    /// and can use `getAuthAccount(signer)` in place of the transaction's `prepare`.
+    /// Nothing is written, so this only catches failures the script reproduces.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn simulate_transaction(
+        &mut self,
+        script: Vec<u8>,
+        arguments: Vec<Vec<u8>>,
+        authorizers: &[&str],
+    ) -> Result<ExecuteScriptResponse> {
+        let mut bound = Vec::with_capacity(authorizers.len() + arguments.len());
+        for authorizer in authorizers {
+            let address = hex::encode(decode_address(authorizer)?);
+            bound.push(Argument::address(address).encode());
+        }
+        bound.extend(arguments);
+        self.execute_script(script, bound, None, None).await
+    }
+    /// Sends the transaction to the blockchain.
+    /// Make sure you signed the transaction with `sign_transaction` first.
+    /// `None` is rejected with `FlowError::UnsignedTransaction` without contacting the node.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn send_transaction(
+        &mut self,
+        transaction: impl Into<Option<Transaction>>,
+    ) -> Result<SendTransactionResponse> {
+        let transaction = match transaction.into() {
+            Some(transaction) => transaction,
+            None => bail!(FlowError::UnsignedTransaction),
+        };
+        let proposal_key = transaction.proposal_key.clone();
+        // send to blockchain. Never resent, as that risks a double submission
+        let request = SendTransactionRequest {
+            transaction: Some(transaction),
+        };
+        let result = self
+            .call(request, false, |mut client, request| async move {
+                client.send_transaction(request).await
+            })
+            .await;
+        // a rejected transaction did not use its sequence number, so the tracker must hand it out again.
+        // After a transport error it may still have been accepted, so the tracker is left alone.
+        if let (Err(error), Some(tracker), Some(key)) =
+            (&result, &self.sequence_tracker, proposal_key)
+        {
+            let rejected = match error.downcast_ref::<tonic::Status>() {
+                Some(status) => {
+                    matches!(
+                        status.code(),
+                        tonic::Code::InvalidArgument | tonic::Code::FailedPrecondition
+                    ) && !matches!(rejection(status), Some(FlowError::DuplicateTransaction(_)))
+                }
+                None => false,
+            };
+            if rejected {
+                tracker.rejected(&key);
+            }
+        }
+        result.map_err(with_rejection)
+    }
+    /// Submits a signed transaction at most once.
+    /// The transaction id is computed locally first, so that when sending fails at the transport (eg. a timeout),
+    /// the node is asked whether it accepted the transaction anyway before it is sent again.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn submit_once(
+        &mut self,
+        transaction: Transaction,
+    ) -> Result<SendTransactionResponse> {
+        let id = transaction_id(&transaction)?;
+        let mut attempts: u32 = 0;
+        loop {
+            attempts += 1;
+            let error = match self.send_transaction(Some(transaction.clone())).await {
+                Ok(response) => return Ok(response),
+                Err(error) => error,
+            };
+            let unknown_outcome = match error.downcast_ref::<tonic::Status>() {
+                Some(status) => is_transport_error(status),
+                None => matches!(
+                    error.downcast_ref::<FlowError>(),
+                    Some(FlowError::Timeout(_))
+                ),
+            };
+            if !unknown_outcome || attempts > self.max_reconnects {
+                return Err(error);
+            }
+            // Access nodes answer ids they have never seen with an unknown status, rather than an error
+            if let Ok(result) = self.get_transaction_result(id.clone()).await {
+                if result.status() != TransactionStatus::Unknown {
+                    return Ok(SendTransactionResponse { id });
+                }
+            }
+        }
+    }
+    /// get a transaction by its id
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(id = %hex::encode(&id))))]
+    pub async fn get_transaction(&mut self, id: Vec<u8>) -> Result<TransactionResponse> {
+        let request = GetTransactionRequest { id };
+        self.call(request, true, |mut client, request| async move {
+            client.get_transaction(request).await
+        })
+        .await
+    }
+    /// get transaction result
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(id = %hex::encode(&id))))]
+    pub async fn get_transaction_result(
+        &mut self,
+        id: Vec<u8>,
+    ) -> Result<TransactionResultResponse> {
+        let request = GetTransactionRequest { id };
+        self.call(request, true, |mut client, request| async move {
+            client.get_transaction_result(request).await
+        })
+        .await
+    }
+    /// The status of the transaction, for polling loops which only wait for it to be sealed.
+    /// The Access API has no status-only RPC, so the full result is still fetched, but its events are dropped as soon as it arrives.
+    /// Errors if the transaction expired or was sealed with an execution error, so a loop stops at either.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(id = %hex::encode(&id))))]
+    pub async fn get_transaction_status(&mut self, id: Vec<u8>) -> Result<TransactionStatus> {
+        let res = self.get_transaction_result(id).await?;
+        match res.status() {
+            TransactionStatus::Sealed if res.status_code != 0 => bail!(FlowError::CadenceRuntime {
+                message: res.error_message,
+                status_code: res.status_code,
+            }),
+            TransactionStatus::Expired => bail!("Transaction expired"),
+            status => Ok(status),
+        }
+    }
+    /// Polls for the result of the transaction until it is sealed, according to `poll`.
+    /// Errors if the transaction expired, failed during execution, or was not sealed in time.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(id = %hex::encode(&id))))]
+    pub async fn wait_for_seal(
+        &mut self,
+        id: Vec<u8>,
+        poll: PollConfig,
+    ) -> Result<TransactionResultResponse> {
+        for _ in 0..poll.max_attempts {
+            let status = self.get_transaction_status(id.clone()).await?;
+            #[cfg(feature = "tracing")]
+            tracing::debug!(status = ?status, "polled transaction");
+            if status == TransactionStatus::Sealed {
+                // only the sealed result is kept, with its events
+                return self.get_transaction_result(id).await;
+            }
+            sleep(poll.interval).await;
+        }
+        bail!(
+            "Transaction {} was not sealed after {} polls",
+            hex::encode(&id),
+            poll.max_attempts
+        )
+    }
+    /// Sends the transaction, then yields each distinct status it moves through, eg. for a progress bar:
+    /// typically `Pending`, `Finalized`, `Executed` and finally `Sealed`, polling according to `poll`.
+    /// The stream ends once the transaction is sealed; a failure during execution, expiry or running out of polls
+    /// is yielded as the final error, like `wait_for_seal`. Errors sending the transaction are returned immediately.
+    pub async fn send_with_updates(
+        &mut self,
+        transaction: Transaction,
+        poll: PollConfig,
+    ) -> Result<impl Stream<Item = Result<TransactionStatus>>> {
+        let id = self.send_transaction(transaction).await?.id;
+        let (updates, stream) = tokio::sync::mpsc::channel(8);
+        let mut connection = self.clone();
+        tokio::spawn(async move {
+            let mut last = None;
+            for _ in 0..poll.max_attempts {
+                let status = match connection.get_transaction_status(id.clone()).await {
+                    Ok(status) => status,
+                    Err(e) => {
+                        let _ = updates.send(Err(e)).await;
+                        return;
+                    }
+                };
+                if last != Some(status) {
+                    last = Some(status);
+                    // the receiver was dropped, so nobody is watching any more
+                    if updates.send(Ok(status)).await.is_err() {
+                        return;
+                    }
+                }
+                if status == TransactionStatus::Sealed {
+                    return;
+                }
+                sleep(poll.interval).await;
+            }
+            let _ = updates
+                .send(Err(anyhow::anyhow!(
+                    "Transaction {} was not sealed after {} polls",
+                    hex::encode(&id),
+                    poll.max_attempts
+                )))
+                .await;
+        });
+        Ok(tokio_stream::wrappers::ReceiverStream::new(stream))
+    }
+    /// get_block accepts either the block_id or block_height. If neither are defined it returns the latest block.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(block_id = ?block_id, block_height = ?block_height)))]
+    pub async fn get_block(
+        &mut self,
+        block_id: Option<String>,
+        block_height: Option<u64>,
+        is_sealed: Option<bool>,
+    ) -> Result<BlockResponse> {
+        if let Some(block_id) = block_id {
+            // IF block_id, use this
+            let request = GetBlockByIdRequest {
+                id: decode_block_id(&block_id)?,
+            };
+            self.call(request, true, |mut client, request| async move {
+                client.get_block_by_id(request).await
+            })
+            .await
+        } else if let Some(height) = block_height {
+            // else IF block_height, use that
+            let request = GetBlockByHeightRequest { height };
+            self.call(request, true, |mut client, request| async move {
+                client.get_block_by_height(request).await
+            })
+            .await
         } else {
             // else, just get latest block
-            if is_sealed.is_some() {
-                let request = tonic::Request::new(GetLatestBlockRequest {
-                    is_sealed: is_sealed.unwrap(),
-                });
-                let response = self.client.get_latest_block(request).await?;
-                Ok(response.into_inner())
-            } else {
-                let request = tonic::Request::new(GetLatestBlockRequest { is_sealed: false });
-                let response = self.client.get_latest_block(request).await?;
-                Ok(response.into_inner())
+            let request = GetLatestBlockRequest {
+                is_sealed: is_sealed.unwrap_or(false),
+            };
+            self.call(request, true, |mut client, request| async move {
+                client.get_latest_block(request).await
+            })
+            .await
+        }
+    }
+    /// The id of the latest block, or of the latest sealed block, eg. to use as a transaction's reference block
+    pub async fn latest_block_id(&mut self, sealed: bool) -> Result<Vec<u8>> {
+        match self.get_block(None, None, Some(sealed)).await?.block {
+            Some(block) => Ok(block.id),
+            None => bail!("Block not found"),
+        }
+    }
+    /// The height of the latest block, or of the latest sealed block
+    pub async fn latest_block_height(&mut self, sealed: bool) -> Result<u64> {
+        match self.get_block(None, None, Some(sealed)).await?.block {
+            Some(block) => Ok(block.height),
+            None => bail!("Block not found"),
+        }
+    }
+    /// Whether a transaction referencing the block would be rejected as expired,
+    /// ie. the block is at least `TRANSACTION_EXPIRY` blocks older than the latest sealed block.
+    pub async fn is_reference_block_expired(&mut self, reference_block_id: &[u8]) -> Result<bool> {
+        let reference = self
+            .get_block(Some(hex::encode(reference_block_id)), None, None)
+            .await?;
+        let latest_height = self.latest_block_height(true).await?;
+        match reference.block {
+            Some(reference) => {
+                Ok(latest_height.saturating_sub(reference.height) >= TRANSACTION_EXPIRY)
             }
+            None => bail!("Block not found"),
         }
     }
     /// retrieve the specified events by type for the given height range
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(event_type = event_type, start_height = start_height, end_height = end_height)
+        )
+    )]
     pub async fn get_events_for_height_range(
         &mut self,
         event_type: &str,
         start_height: u64,
         end_height: u64,
     ) -> Result<EventsResponse> {
-        let request = tonic::Request::new(GetEventsForHeightRangeRequest {
+        let request = GetEventsForHeightRangeRequest {
             r#type: event_type.to_owned(),
             start_height,
             end_height,
-        });
-        let response = self.client.get_events_for_height_range(request).await?;
-        Ok(response.into_inner())
+        };
+        self.call(request, true, |mut client, request| async move {
+            client.get_events_for_height_range(request).await
+        })
+        .await
+    }
+    /// Fetches the events of each type in the height range, requesting the types concurrently.
+    /// Returns each type with its events, in the order of `types`.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(
+            skip_all,
+            fields(types = ?types, start_height = start_height, end_height = end_height)
+        )
+    )]
+    pub async fn get_events_for_types(
+        &mut self,
+        types: &[&str],
+        start_height: u64,
+        end_height: u64,
+    ) -> Result<Vec<(String, Vec<flow::Event>)>> {
+        let types: Vec<String> = types
+            .iter()
+            .map(|event_type| event_type.to_string())
+            .collect();
+        self.call_concurrently(types, |mut connection, event_type| async move {
+            let response = connection
+                .get_events_for_height_range(&event_type, start_height, end_height)
+                .await?;
+            let events = response
+                .results
+                .into_iter()
+                .flat_map(|block| block.events)
+                .collect();
+            Ok((event_type, events))
+        })
+        .await
+        .into_iter()
+        .collect()
+    }
+    /// Fetches the events of the type from the block after the cursor up to the latest sealed block,
+    /// in chunks of `MAX_EVENT_HEIGHT_RANGE`, returning them in height order along with the advanced cursor.
+    /// When there are no new sealed blocks the events are empty and the cursor is unchanged.
+    /// Each chunk is a round trip, so start the cursor near the heights of interest; see `EventCursor::at`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(event_type = event_type, last_height = cursor.last_height)))]
+    pub async fn poll_events_since(
+        &mut self,
+        event_type: &str,
+        cursor: EventCursor,
+    ) -> Result<(Vec<flow::Event>, EventCursor)> {
+        let latest_height = self.latest_block_height(true).await?;
+        let mut events = vec![];
+        let mut start_height = cursor.last_height + 1;
+        while start_height <= latest_height {
+            let end_height = latest_height.min(start_height + MAX_EVENT_HEIGHT_RANGE - 1);
+            let response = self
+                .get_events_for_height_range(event_type, start_height, end_height)
+                .await?;
+            for block in response.results {
+                events.extend(block.events);
+            }
+            start_height = end_height + 1;
+        }
+        let cursor = EventCursor {
+            last_height: latest_height.max(cursor.last_height),
+        };
+        Ok((events, cursor))
     }
     /// retrieve the specified events by type for the given blocks
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(event_type = event_type)))]
     pub async fn get_events_for_block_ids(
         &mut self,
         event_type: &str,
         ids: Vec<Vec<u8>>,
     ) -> Result<EventsResponse> {
-        let request = tonic::Request::new(GetEventsForBlockIdsRequest {
+        let request = GetEventsForBlockIdsRequest {
             r#type: event_type.to_owned(),
             block_ids: ids,
-        });
-        let response = self.client.get_events_for_block_i_ds(request).await?;
-        Ok(response.into_inner())
+        };
+        self.call(request, true, |mut client, request| async move {
+            client.get_events_for_block_i_ds(request).await
+        })
+        .await
+    }
+    /// retrieve the specified events by type for the given hex-encoded block ids, as accepted by `get_block`
+    pub async fn get_events_for_block_ids_hex(
+        &mut self,
+        event_type: &str,
+        ids: &[String],
+    ) -> Result<EventsResponse> {
+        let mut block_ids = Vec::with_capacity(ids.len());
+        for id in ids {
+            block_ids.push(decode_block_id(id)?);
+        }
+        self.get_events_for_block_ids(event_type, block_ids).await
+    }
+    /// Fetches the node's software version, protocol version and spork, eg. to check the node is compatible,
+    /// or notice that a spork has moved the network to a new root block. Nodes predating the RPC fail with `Unimplemented`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_node_version_info(&mut self) -> Result<NodeVersionInfoResponse> {
+        self.call(
+            GetNodeVersionInfoRequest {},
+            true,
+            |mut client, request| async move { client.get_node_version_info(request).await },
+        )
+        .await
     }
     /// retrieve the specified collections
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
     pub async fn get_collection(
         &mut self,
         collection_id: Vec<u8>,
     ) -> Result<CollectionResponse> {
-        let request = tonic::Request::new(GetCollectionByIdRequest { id: collection_id });
-        let response = self.client.get_collection_by_id(request).await?;
-        Ok(response.into_inner())
+        let request = GetCollectionByIdRequest { id: collection_id };
+        self.call(request, true, |mut client, request| async move {
+            client.get_collection_by_id(request).await
+        })
+        .await
     }
-    /// Create an account with the given `account_keys` and `payer`
-    pub async fn create_account(
+    /// Fetches the collection, then each of its transactions, returning them in the collection's order.
+    /// This costs one round trip for the collection and one per transaction; the transactions are requested concurrently,
+    /// up to `MAX_CONCURRENT_REQUESTS` at a time.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all))]
+    pub async fn get_collection_transactions(
         &mut self,
-        account_keys: Vec<String>,
-        payer: &str,
-        payer_private_key: &str,
-        key_id: u32,
-    ) -> Result<flow::Account> {
-        let create_account_template = b"
-        transaction(publicKeys: [String], contracts: {String: String}) {
-            prepare(signer: AuthAccount) {
-                let acct = AuthAccount(payer: signer)
-        
-                for key in publicKeys {
-                    acct.addPublicKey(key.decodeHex())
-                }
-        
-                for contract in contracts.keys {
-                    acct.contracts.add(name: contract, code: contracts[contract]!.decodeHex())
-                }
+        collection_id: Vec<u8>,
+    ) -> Result<Vec<TransactionResponse>> {
+        let collection = match self.get_collection(collection_id).await?.collection {
+            Some(collection) => collection,
+            None => bail!("Collection not found"),
+        };
+        self.call_concurrently(
+            collection.transaction_ids,
+            |mut connection, id| async move { connection.get_transaction(id).await },
+        )
+        .await
+        .into_iter()
+        .collect()
+    }
+    /// get_block_transactions fetches the block (see `get_block`), then each of its collections,
+    /// and returns the ids of all the transactions in the block, in order.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(block_id = ?block_id, block_height = ?block_height)))]
+    pub async fn get_block_transactions(
+        &mut self,
+        block_id: Option<String>,
+        block_height: Option<u64>,
+    ) -> Result<Vec<Vec<u8>>> {
+        let block = match self.get_block(block_id, block_height, None).await?.block {
+            Some(block) => block,
+            None => bail!("Block not found"),
+        };
+        let mut transaction_ids: Vec<Vec<u8>> = vec![];
+        for guarantee in block.collection_guarantees {
+            if let Some(collection) = self.get_collection(guarantee.collection_id).await?.collection {
+                transaction_ids.extend(collection.transaction_ids);
             }
-        }";
-
-        let latest_block: BlockResponse = self.get_block(None, None, Some(false)).await?;
-        let account: flow::Account = self.get_account(payer).await?.account.unwrap();
-        let proposer = TransactionProposalKey {
-            address: hex::decode(payer).unwrap(),
-            key_id,
-            sequence_number: account.keys[key_id as usize].sequence_number as u64,
-        };
-        let keys_arg = process_keys_args(account_keys);
-        // empty contracts for now - will implement in the future
-        let contracts_arg = Argument::dictionary(vec![]);
-        let keys_arg = json!(keys_arg);
-        let contracts_arg = json!(contracts_arg);
-        let transaction: Transaction = build_transaction(
-            create_account_template.to_vec(),
-            vec![to_vec(&keys_arg)?, to_vec(&contracts_arg)?],
-            latest_block.block.unwrap().id,
-            1000,
+        }
+        Ok(transaction_ids)
+    }
+    /// Builds a transaction like `build_transaction`, referencing the latest block
+    pub async fn build_tx(
+        &mut self,
+        script: Vec<u8>,
+        arguments: Vec<Vec<u8>>,
+        gas_limit: u64,
+        proposer: TransactionProposalKey,
+        authorizers: Vec<String>,
+        payer: String,
+    ) -> Result<Transaction> {
+        let reference_block_id = self.latest_block_id(false).await?;
+        build_transaction(
+            script,
+            arguments,
+            reference_block_id,
+            gas_limit,
             proposer,
-            vec![payer.to_owned()],
-            payer.to_owned(),
+            authorizers,
+            payer,
+        )
+        .await
+    }
+    /// Builds, signs and sends a transaction running `script`, authorized by each of `authorizers`.
+    /// `payer` is the proposer and payer: the first of `payer_keys` is the proposal key, and every key signs the envelope.
+    /// Each authorizer's keys sign the payload, except the payer's, whose envelope signature covers its authorization;
+    /// so a payer that also authorizes is listed with no keys, eg. `&[(payer, &[])]`.
+    /// The proposal key's `sequence_number` is fetched from the account unless provided.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(payer = payer)))]
+    pub async fn execute_transaction(
+        &mut self,
+        script: &[u8],
+        arguments: Vec<Vec<u8>>,
+        authorizers: &[(&str, &[Sign])],
+        payer: &str,
+        payer_keys: &[Sign],
+        sequence_number: Option<u64>,
+    ) -> Result<SendTransactionResponse> {
+        let transaction = self
+            .authorized_transaction(
+                script,
+                arguments,
+                authorizers,
+                payer,
+                payer_keys,
+                sequence_number,
+            )
+            .await?;
+        self.send_transaction(transaction).await
+    }
+    /// Builds and signs a transaction where `payer` is the proposer, payer and sole authorizer.
+    /// The first of `payer_keys` is the proposal key, and every key signs the envelope, so weighted payer keys can add up to 1000.
+    /// The proposal key's `sequence_number` is fetched from the account unless provided.
+    async fn payer_transaction(
+        &mut self,
+        script: &[u8],
+        arguments: Vec<Vec<u8>>,
+        payer: &str,
+        payer_keys: &[Sign],
+        sequence_number: Option<u64>,
+    ) -> Result<Transaction> {
+        self.authorized_transaction(
+            script,
+            arguments,
+            &[(payer, &[])],
+            payer,
+            payer_keys,
+            sequence_number,
         )
-        .await?;
-        let signature = Sign {
-            address: payer.to_owned(),
+        .await
+    }
+    /// Builds and signs a transaction, as described by `execute_transaction`
+    async fn authorized_transaction(
+        &mut self,
+        script: &[u8],
+        arguments: Vec<Vec<u8>>,
+        authorizers: &[(&str, &[Sign])],
+        payer: &str,
+        payer_keys: &[Sign],
+        sequence_number: Option<u64>,
+    ) -> Result<Transaction> {
+        let key_id = match payer_keys.first() {
+            Some(key) => key.key_id,
+            None => bail!("At least one payer key is required"),
+        };
+        // only fetch the account when the caller did not already know the sequence number
+        let sequence_number = match (sequence_number, self.sequence_tracker.clone()) {
+            (Some(sequence_number), _) => sequence_number,
+            (None, Some(tracker)) => tracker.next(self, payer, key_id).await?,
+            (None, None) => {
+                let account: flow::Account = self.get_account(payer).await?.into_account()?;
+                key_sequence_number(&account, key_id)?
+            }
+        };
+        let payer_address = decode_address(payer)?;
+        let proposer = TransactionProposalKey {
+            address: payer_address.clone(),
             key_id,
-            private_key: payer_private_key.to_owned(),
+            sequence_number,
         };
-        let transaction: Option<Transaction> =
-            sign_transaction(transaction, vec![], vec![&signature]).await?;
+        let mut payload_signers: Vec<&Sign> = vec![];
+        for (authorizer, keys) in authorizers {
+            if decode_address(authorizer)? != payer_address {
+                if keys.is_empty() {
+                    bail!("Authorizer {} has no keys to sign with", authorizer);
+                }
+                payload_signers.extend(keys.iter());
+            }
+        }
+        let transaction: Transaction = self
+            .build_tx(
+                script.to_vec(),
+                arguments,
+                self.gas_limit,
+                proposer,
+                authorizers
+                    .iter()
+                    .map(|(authorizer, _)| authorizer.to_string())
+                    .collect(),
+                payer.to_owned(),
+            )
+            .await?;
+        sign_transaction_with_domain_tag(
+            transaction,
+            payload_signers,
+            payer_keys.iter().collect(),
+            self.domain_tag,
+        )
+        .await
+    }
+    /// Transfers `amount` FLOW from the signer's account to `to`, and waits for the transaction to be sealed
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(amount = amount, to = to)))]
+    pub async fn transfer_flow(
+        &mut self,
+        amount: f64,
+        to: &str,
+        from: &Sign,
+        poll: PollConfig,
+    ) -> Result<TransactionResultResponse> {
+        let transfer_template = self
+            .templates
+            .transfer_flow
+            .replace("0xFUNGIBLETOKENADDRESS", self.network.fungible_token_address())
+            .replace("0xFLOWTOKENADDRESS", self.network.flow_token_address());
+        let amount_arg = Argument::ufix64(amount)?;
+        // a mistyped recipient is rejected before anything is signed
+        let to_arg = Argument::address(hex::encode(decode_address(to)?));
+        let transaction: Transaction = self
+            .payer_transaction(
+                transfer_template.as_bytes(),
+                vec![amount_arg.encode(), to_arg.encode()],
+                &from.address,
+                std::slice::from_ref(from),
+                None,
+            )
+            .await?;
+        let transaction: SendTransactionResponse = self.send_transaction(transaction).await?;
+        self.wait_for_seal(transaction.id, poll).await
+    }
+    /// Create an account with the given `keys` and `payer`. Each key has its own algorithms and weight,
+    /// eg. three keys weighted 500, 500 and 1000, so that either the first two together or the third alone can sign for it.
+    /// The keys are added through the `keys.add` API, like `add_public_key`, with either `CadenceVersion`.
+    /// Nothing is printed; with the `tracing` feature the submitted transaction id is logged at debug level.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(payer = payer)))]
+    pub async fn create_account(
+        &mut self,
+        keys: &[PublicKeyArgs],
+        payer: &str,
+        payer_keys: Vec<Sign>,
+        sequence_number: Option<u64>,
+    ) -> Result<flow::Account> {
+        let address = self
+            .create_account_address(keys, payer, payer_keys, sequence_number)
+            .await?;
+        self.get_account(&address).await?.into_account()
+    }
+    /// Create an account like `create_account`, returning only its hex-encoded address, taken from the `flow.AccountCreated` event.
+    /// This saves the round trip that fetches the new account.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(payer = payer)))]
+    pub async fn create_account_address(
+        &mut self,
+        keys: &[PublicKeyArgs],
+        payer: &str,
+        payer_keys: Vec<Sign>,
+        sequence_number: Option<u64>,
+    ) -> Result<String> {
+        let template = self.templates.create_account.clone();
+        let transaction: Transaction = self
+            .payer_transaction(
+                template.as_bytes(),
+                PublicKeyArgs::array_arguments(keys)?,
+                payer,
+                &payer_keys,
+                sequence_number,
+            )
+            .await?;
         let transaction: SendTransactionResponse = self.send_transaction(transaction).await?;
         // poll for transaction completion
         let mut time: u64 = 50;
         let mut i = 0;
-        println!("{}", hex::encode(transaction.id.to_vec()));
+        #[cfg(feature = "tracing")]
+        tracing::debug!(id = %hex::encode(&transaction.id), "submitted transaction");
         while i < 50 {
             i += 1;
             sleep(Duration::from_millis(time)).await;
             let res = self.get_transaction_result(transaction.id.to_vec()).await?;
-            match res.status {
-                0 | 1 | 2 | 3 => {
+            #[cfg(feature = "tracing")]
+            tracing::debug!(status = ?res.status(), "polled transaction");
+            match res.status() {
+                TransactionStatus::Unknown
+                | TransactionStatus::Pending
+                | TransactionStatus::Finalized
+                | TransactionStatus::Executed => {
                     time += 200;
                 }
-                4 => {
-                    if res.status_code == 1 {
+                TransactionStatus::Sealed => {
+                    if res.status_code != 0 {
                         // stop execution, error.
-                        bail!("Error during execution");
+                        bail!(FlowError::CadenceRuntime {
+                            message: res.error_message,
+                            status_code: res.status_code,
+                        });
                     }
-                    let new_account_address: flow::Event = res
-                        .events
-                        .into_iter()
-                        .filter(|x| x.r#type == "flow.AccountCreated")
-                        .collect::<Vec<flow::Event>>()
-                        .pop()
-                        .unwrap();
-                    let payload: Value = from_slice(&new_account_address.payload)?;
-                    let address: String = payload["value"]["fields"][0]["value"]["value"]
-                        .to_string()
-                        .split_at(3)
-                        .1
-                        .to_string()
-                        .split_at(16)
-                        .0
-                        .to_string();
-                    let acct: flow::Account = self
-                        .get_account(&address)
-                        .await?
-                        .account
-                        .expect("could not get newly created account");
-                    return Ok(acct);
+                    return created_account_address(&res.events);
                 }
                 _ => bail!("Cadence Runtime Error"),
             }
         }
         bail!("Could not produce result")
     }
-    /// add a key
+    /// add a key. With `CadenceVersion::Legacy` this is either a raw 64 byte public key or an encoded account key, see `encode_account_key`;
+    /// with `CadenceVersion::Cadence1` it must be the raw public key.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(payer = payer)))]
     pub async fn add_key(
         &mut self,
         public_key_to_add: &str,
         payer: &str,
-        payer_private_key: &str,
-        key_id: u32,
+        payer_keys: Vec<Sign>,
+        sequence_number: Option<u64>,
     ) -> Result<flow::SendTransactionResponse> {
-        let update_contract_template = b"
-        transaction(publicKey: String) {
-            prepare(signer: AuthAccount) {
-                signer.addPublicKey(publicKey.decodeHex())
-            }
-        }
-        ";
-        let latest_block: BlockResponse = self.get_block(None, None, Some(false)).await?;
-        let account: flow::Account = self.get_account(payer).await?.account.unwrap();
-        let proposer = TransactionProposalKey {
-            address: hex::decode(payer).unwrap(),
-            key_id,
-            sequence_number: account.keys[key_id as usize].sequence_number as u64,
-        };
-        let public_key_to_add_arg = Argument::str(public_key_to_add);
-        let transaction: Transaction = build_transaction(
-            update_contract_template.to_vec(),
-            vec![public_key_to_add_arg.encode_str()],
-            latest_block.block.unwrap().id,
-            1000,
-            proposer,
-            vec![payer.to_owned()],
-            payer.to_owned(),
-        )
-        .await?;
-        let signature = Sign {
-            address: payer.to_owned(),
-            key_id,
-            private_key: payer_private_key.to_owned(),
+        let template = self.templates.add_key.clone();
+        let public_key_to_add = match self.cadence_version {
+            CadenceVersion::Legacy => encode_account_key(public_key_to_add),
+            CadenceVersion::Cadence1 => public_key_to_add.to_owned(),
         };
-        let transaction: Option<Transaction> =
-            sign_transaction(transaction, vec![], vec![&signature]).await?;
+        let public_key_to_add_arg = Argument::str(&public_key_to_add);
+        let transaction: Transaction = self
+            .payer_transaction(
+                template.as_bytes(),
+                vec![public_key_to_add_arg.encode()],
+                payer,
+                &payer_keys,
+                sequence_number,
+            )
+            .await?;
         let transaction: SendTransactionResponse = self.send_transaction(transaction).await?;
 
         Ok(transaction)
     }
-    /// remove a key
+    /// add a key through the `keys.add` API, with the algorithms and weight given by `key`
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(payer = payer)))]
+    pub async fn add_public_key(
+        &mut self,
+        key: &PublicKeyArgs,
+        payer: &str,
+        payer_keys: Vec<Sign>,
+        sequence_number: Option<u64>,
+    ) -> Result<flow::SendTransactionResponse> {
+        let template = self.templates.add_public_key.clone();
+        let transaction: Transaction = self
+            .payer_transaction(
+                template.as_bytes(),
+                key.arguments()?,
+                payer,
+                &payer_keys,
+                sequence_number,
+            )
+            .await?;
+        self.send_transaction(transaction).await
+    }
+    /// remove a key. With `CadenceVersion::Legacy` this calls the deprecated `removePublicKey`; see `revoke_key`
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(payer = payer)))]
     pub async fn remove_key(
         &mut self,
         key_to_remove: u64,
         payer: &str,
-        payer_private_key: &str,
-        key_id: u32,
+        payer_keys: Vec<Sign>,
+        sequence_number: Option<u64>,
     ) -> Result<flow::SendTransactionResponse> {
-        let update_contract_template = b"
-        transaction(keyIndex: Int) {
-            prepare(signer: AuthAccount) {
-                signer.removePublicKey(keyIndex)
-            }
-        }
-        ";
-        let latest_block: BlockResponse = self.get_block(None, None, Some(false)).await?;
-        let account: flow::Account = self.get_account(payer).await?.account.unwrap();
-        let proposer = TransactionProposalKey {
-            address: hex::decode(payer).unwrap(),
-            key_id,
-            sequence_number: account.keys[key_id as usize].sequence_number as u64,
-        };
+        let template = self.templates.remove_key.clone();
         let key_to_remove_arg = Argument::uint64(key_to_remove);
-        let transaction: Transaction = build_transaction(
-            update_contract_template.to_vec(),
-            vec![key_to_remove_arg.encode()],
-            latest_block.block.unwrap().id,
-            1000,
-            proposer,
-            vec![payer.to_owned()],
-            payer.to_owned(),
-        )
-        .await?;
-        let signature = Sign {
-            address: payer.to_owned(),
-            key_id,
-            private_key: payer_private_key.to_owned(),
-        };
-        let transaction: Option<Transaction> =
-            sign_transaction(transaction, vec![], vec![&signature]).await?;
+        let transaction: Transaction = self
+            .payer_transaction(
+                template.as_bytes(),
+                vec![key_to_remove_arg.encode()],
+                payer,
+                &payer_keys,
+                sequence_number,
+            )
+            .await?;
         let transaction: SendTransactionResponse = self.send_transaction(transaction).await?;
 
         Ok(transaction)
     }
-    /// add a contract
+    /// revoke a key with `keys.revoke`, which works both before and after Cadence 1.0.
+    /// The transaction fails if the account has no key at `key_index`. Revoked keys stay on the account, marked `revoked`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(payer = payer)))]
+    pub async fn revoke_key(
+        &mut self,
+        key_index: u64,
+        payer: &str,
+        payer_keys: Vec<Sign>,
+        sequence_number: Option<u64>,
+    ) -> Result<flow::SendTransactionResponse> {
+        let template = self.templates.revoke_key.clone();
+        let transaction: Transaction = self
+            .payer_transaction(
+                template.as_bytes(),
+                vec![Argument::uint64(key_index).encode()],
+                payer,
+                &payer_keys,
+                sequence_number,
+            )
+            .await?;
+        self.send_transaction(transaction).await
+    }
+    /// add a contract. `contract_code` is the hex-encoded Cadence source; see `add_contract_source` to pass the source as-is.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(payer = payer)))]
     pub async fn add_contract(
         &mut self,
         contract_name: &str,
         contract_code: &str,
         payer: &str,
-        payer_private_key: &str,
-        key_id: u32,
+        payer_keys: Vec<Sign>,
+        sequence_number: Option<u64>,
     ) -> Result<flow::SendTransactionResponse> {
-        let update_contract_template = b"
-        transaction(name: String, code: String) {
-            prepare(signer: AuthAccount) {
-                signer.contracts.add(name: name, code: code.decodeHex())
-            }
-        }
-        ";
-        let latest_block: BlockResponse = self.get_block(None, None, Some(false)).await?;
-        let account: flow::Account = self.get_account(payer).await?.account.unwrap();
-        let proposer = TransactionProposalKey {
-            address: hex::decode(payer).unwrap(),
-            key_id,
-            sequence_number: account.keys[key_id as usize].sequence_number as u64,
-        };
+        let template = self.templates.add_contract.clone();
         let contract_name_arg = Argument::str(contract_name);
         let contract_code_arg = Argument::str(contract_code);
-        let transaction: Transaction = build_transaction(
-            update_contract_template.to_vec(),
-            vec![
-                contract_name_arg.encode_str(),
-                contract_code_arg.encode_str(),
-            ],
-            latest_block.block.unwrap().id,
-            1000,
-            proposer,
-            vec![payer.to_owned()],
-            payer.to_owned(),
-        )
-        .await?;
-        let signature = Sign {
-            address: payer.to_owned(),
-            key_id,
-            private_key: payer_private_key.to_owned(),
-        };
-        let transaction: Option<Transaction> =
-            sign_transaction(transaction, vec![], vec![&signature]).await?;
+        let transaction: Transaction = self
+            .payer_transaction(
+                template.as_bytes(),
+                vec![contract_name_arg.encode(), contract_code_arg.encode()],
+                payer,
+                &payer_keys,
+                sequence_number,
+            )
+            .await?;
         let transaction: SendTransactionResponse = self.send_transaction(transaction).await?;
 
         Ok(transaction)
     }
-    /// update a contract
+    /// update a contract. `contract_code` is the hex-encoded Cadence source; see `update_contract_source` to pass the source as-is.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(payer = payer)))]
     pub async fn update_contract(
         &mut self,
         contract_name: &str,
         contract_code: &str,
         payer: &str,
-        payer_private_key: &str,
-        key_id: u32,
+        payer_keys: Vec<Sign>,
+        sequence_number: Option<u64>,
     ) -> Result<flow::SendTransactionResponse> {
-        let update_contract_template = b"
-        transaction(name: String, code: String) {
-            prepare(signer: AuthAccount) {
-                signer.contracts.update__experimental(name: name, code: code.decodeHex())
-            }
-        }
-        ";
-        let latest_block: BlockResponse = self.get_block(None, None, Some(false)).await?;
-        let account: flow::Account = self.get_account(payer).await?.account.unwrap();
-        let proposer = TransactionProposalKey {
-            address: hex::decode(payer).unwrap(),
-            key_id,
-            sequence_number: account.keys[key_id as usize].sequence_number as u64,
-        };
+        let template = self.templates.update_contract.clone();
         let contract_name_arg = Argument::str(contract_name);
         let contract_code_arg = Argument::str(contract_code);
-        let transaction: Transaction = build_transaction(
-            update_contract_template.to_vec(),
-            vec![
-                contract_name_arg.encode_str(),
-                contract_code_arg.encode_str(),
-            ],
-            latest_block.block.unwrap().id,
-            1000,
-            proposer,
-            vec![payer.to_owned()],
-            payer.to_owned(),
-        )
-        .await?;
-        let signature = Sign {
-            address: payer.to_owned(),
-            key_id,
-            private_key: payer_private_key.to_owned(),
-        };
-        let transaction: Option<Transaction> =
-            sign_transaction(transaction, vec![], vec![&signature]).await?;
+        let transaction: Transaction = self
+            .payer_transaction(
+                template.as_bytes(),
+                vec![contract_name_arg.encode(), contract_code_arg.encode()],
+                payer,
+                &payer_keys,
+                sequence_number,
+            )
+            .await?;
         let transaction: SendTransactionResponse = self.send_transaction(transaction).await?;
 
         Ok(transaction)
     }
+    /// add a contract like `add_contract`, then wait for the transaction to be sealed.
+    /// A contract which fails to deploy, eg. because it does not compile, is returned as `FlowError::CadenceRuntime` with the error message.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(payer = payer)))]
+    pub async fn add_contract_and_wait(
+        &mut self,
+        contract_name: &str,
+        contract_code: &str,
+        payer: &str,
+        payer_keys: Vec<Sign>,
+        sequence_number: Option<u64>,
+        poll: PollConfig,
+    ) -> Result<TransactionResultResponse> {
+        let transaction = self
+            .add_contract(
+                contract_name,
+                contract_code,
+                payer,
+                payer_keys,
+                sequence_number,
+            )
+            .await?;
+        self.wait_for_seal(transaction.id, poll).await
+    }
+    /// update a contract like `update_contract`, then wait for the transaction to be sealed.
+    /// An update which is rejected, eg. because the contract does not compile, is returned as `FlowError::CadenceRuntime` with the error message.
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(payer = payer)))]
+    pub async fn update_contract_and_wait(
+        &mut self,
+        contract_name: &str,
+        contract_code: &str,
+        payer: &str,
+        payer_keys: Vec<Sign>,
+        sequence_number: Option<u64>,
+        poll: PollConfig,
+    ) -> Result<TransactionResultResponse> {
+        let transaction = self
+            .update_contract(
+                contract_name,
+                contract_code,
+                payer,
+                payer_keys,
+                sequence_number,
+            )
+            .await?;
+        self.wait_for_seal(transaction.id, poll).await
+    }
+    /// add a contract from its Cadence source, eg. the contents of a `.cdc` file
+    pub async fn add_contract_source(
+        &mut self,
+        contract_name: &str,
+        contract_source: &str,
+        payer: &str,
+        payer_keys: Vec<Sign>,
+        sequence_number: Option<u64>,
+    ) -> Result<flow::SendTransactionResponse> {
+        self.add_contract(
+            contract_name,
+            &hex::encode(contract_source),
+            payer,
+            payer_keys,
+            sequence_number,
+        )
+        .await
+    }
+    /// update a contract from its Cadence source, eg. the contents of a `.cdc` file
+    pub async fn update_contract_source(
+        &mut self,
+        contract_name: &str,
+        contract_source: &str,
+        payer: &str,
+        payer_keys: Vec<Sign>,
+        sequence_number: Option<u64>,
+    ) -> Result<flow::SendTransactionResponse> {
+        self.update_contract(
+            contract_name,
+            &hex::encode(contract_source),
+            payer,
+            payer_keys,
+            sequence_number,
+        )
+        .await
+    }
     /// remove a contract
+    #[cfg_attr(feature = "tracing", tracing::instrument(skip_all, fields(payer = payer)))]
     pub async fn remove_contract(
         &mut self,
         contract_name: &str,
         payer: &str,
-        payer_private_key: &str,
-        key_id: u32,
+        payer_keys: Vec<Sign>,
+        sequence_number: Option<u64>,
     ) -> Result<flow::SendTransactionResponse> {
-        let update_contract_template = b"
-        transaction(name: String) {
-            prepare(signer: AuthAccount) {
-                signer.contracts.remove(name: name)
+        let template = self.templates.remove_contract.clone();
+        let contract_name_arg = Argument::str(contract_name);
+        let transaction: Transaction = self
+            .payer_transaction(
+                template.as_bytes(),
+                vec![contract_name_arg.encode()],
+                payer,
+                &payer_keys,
+                sequence_number,
+            )
+            .await?;
+        let transaction: SendTransactionResponse = self.send_transaction(transaction).await?;
+
+        Ok(transaction)
+    }
+}
+
+// ****************************************************
+// Utility Functionality
+// ****************************************************
+
+use serde::Serialize;
+use unicode_segmentation::UnicodeSegmentation;
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+pub use serde_json::{from_slice, json, to_vec, Value};
+#[cfg(feature = "transport")]
+use tokio::time::{sleep, Duration};
+
+/// This is our argument builder.
+#[derive(Serialize)]
+pub struct Argument<T> {
+    r#type: &'static str,
+    value: T,
+}
+/// An array argument, as returned in pairs by `Argument::address_amount_pairs`
+type ArrayArgument = Argument<Vec<Value>>;
+
+/// Argument builder assuming a vec<String>
+impl Argument<Vec<Value>> {
+    /// Argument from array
+    pub fn array(values: Vec<Value>) -> Argument<Vec<Value>> {
+        Argument {
+            r#type: "Array",
+            value: values,
+        }
+    }
+    /// Argument for a `[UInt8]` parameter, with one `UInt8` per byte
+    pub fn bytes(data: &[u8]) -> Argument<Vec<Value>> {
+        Argument::array(
+            data.iter()
+                .map(|byte| json!({"type": "UInt8", "value": byte.to_string()}))
+                .collect(),
+        )
+    }
+    /// Argument for a `[UInt64]` parameter
+    pub fn uint64_array(values: &[u64]) -> Argument<Vec<Value>> {
+        Argument::array(
+            values
+                .iter()
+                .map(|value| Argument::uint64(*value).to_value())
+                .collect(),
+        )
+    }
+    /// Argument for an `[Address]` parameter, from hex-encoded addresses with or without the `0x` prefix
+    pub fn address_array(addresses: &[&str]) -> Argument<Vec<Value>> {
+        Argument::array(
+            addresses
+                .iter()
+                .map(|address| Argument::address((*address).to_owned()).to_value())
+                .collect(),
+        )
+    }
+    /// Argument for a `[String]` parameter
+    pub fn string_array(values: &[&str]) -> Argument<Vec<Value>> {
+        Argument::array(
+            values
+                .iter()
+                .map(|value| Argument::str(value).to_value())
+                .collect(),
+        )
+    }
+    /// Argument for a `[UFix64]` parameter. Errors if any value is not a valid UFix64, see `ufix64`.
+    pub fn ufix64_array(values: &[f64]) -> Result<Argument<Vec<Value>>> {
+        Ok(Argument::array(
+            values
+                .iter()
+                .map(|value| Ok(Argument::ufix64(*value)?.to_value()))
+                .collect::<Result<Vec<Value>>>()?,
+        ))
+    }
+    /// Arguments for the `addresses: [Address], amounts: [UFix64]` parameters of a batch transfer, eg. an airdrop.
+    /// Building both from one list of pairs keeps them the same length and in the same order.
+    /// Errors if an address is not a valid Flow address or an amount is not a valid UFix64.
+    pub fn address_amount_pairs(pairs: &[(&str, f64)]) -> Result<(ArrayArgument, ArrayArgument)> {
+        let mut addresses = Vec::with_capacity(pairs.len());
+        let mut amounts = Vec::with_capacity(pairs.len());
+        for (address, amount) in pairs {
+            let address = hex::encode(decode_address(address)?);
+            addresses.push(Argument::address(address).to_value());
+            amounts.push(Argument::ufix64(*amount)?.to_value());
+        }
+        Ok((Argument::array(addresses), Argument::array(amounts)))
+    }
+    /// Argument from dictionary `Vec<(Value, Value)>`.
+    /// Keys and values must already be encoded arguments, eg. `json!(Argument::str("key"))`
+    pub fn dictionary(values: Vec<(Value, Value)>) -> Argument<Vec<Value>> {
+        Argument {
+            r#type: "Dictionary",
+            value: values
+                .into_iter()
+                .map(|(x, y)| json!({"key":x, "value":y}))
+                .collect(),
+        }
+    }
+    /// Argument from a `{String: String}` dictionary `Vec<(String, String)>`
+    pub fn dictionary_str(values: Vec<(String, String)>) -> Argument<Vec<Value>> {
+        Argument::dictionary(
+            values
+                .into_iter()
+                .map(|(x, y)| (json!(Argument::string(x)), json!(Argument::string(y))))
+                .collect(),
+        )
+    }
+}
+/// The domain of a Cadence `Path`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathDomain {
+    Storage,
+    Public,
+    Private,
+}
+impl PathDomain {
+    /// The domain as it appears in JSON-Cadence, eg. `storage`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PathDomain::Storage => "storage",
+            PathDomain::Public => "public",
+            PathDomain::Private => "private",
+        }
+    }
+}
+/// The kind of a Cadence composite value
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CompositeKind {
+    Struct,
+    Resource,
+    Event,
+    Enum,
+}
+impl CompositeKind {
+    /// The kind as it appears in JSON-Cadence, eg. `Struct`
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CompositeKind::Struct => "Struct",
+            CompositeKind::Resource => "Resource",
+            CompositeKind::Event => "Event",
+            CompositeKind::Enum => "Enum",
+        }
+    }
+}
+/// Argument builder for values with a nested structure, such as paths
+impl Argument<Value> {
+    /// Argument from a path, eg. `/storage/flowTokenVault`
+    pub fn path(domain: PathDomain, identifier: &str) -> Argument<Value> {
+        Argument {
+            r#type: "Path",
+            value: json!({"domain": domain.as_str(), "identifier": identifier}),
+        }
+    }
+    /// Argument for an optional parameter, eg. `Argument::optional(Some(Argument::uint64(1).to_value()))`
+    pub fn optional(value: Option<Value>) -> Argument<Value> {
+        Argument {
+            r#type: "Optional",
+            value: value.unwrap_or(Value::Null),
+        }
+    }
+    /// The `Void` argument
+    pub fn void() -> Argument<Value> {
+        Argument {
+            r#type: "Void",
+            value: Value::Null,
+        }
+    }
+    /// Argument from a static type, eg. `Int`, `[String]`, `&A.0ae53cb6e3f42a79.FlowToken.Vault` or `{String: UInt64}`.
+    /// The identifiers do not say whether a qualified type (`A.<address>.<Contract>.<Type>`) is a struct or a resource,
+    /// so they are encoded as resources, as NFTs and vaults are. Use `type_value_with_kind` for structs.
+    /// Errors if the type cannot be parsed.
+    pub fn type_value(static_type: &str) -> Result<Argument<Value>> {
+        Argument::type_value_with_kind(static_type, CompositeKind::Resource)
+    }
+    /// Argument from a static type like `type_value`, with its qualified types encoded as `composite_kind`
+    pub fn type_value_with_kind(
+        static_type: &str,
+        composite_kind: CompositeKind,
+    ) -> Result<Argument<Value>> {
+        Ok(Argument {
+            r#type: "Type",
+            value: json!({ "staticType": static_type_json(static_type, composite_kind, false)? }),
+        })
+    }
+    /// Argument from a capability. `path` should be built with `Argument::path`
+    /// and `address` is the hex-encoded address of the account holding the capability, with or without `0x`.
+    /// `borrow_type` is a reference type, eg. `&A.0ae53cb6e3f42a79.FlowToken.Vault`, encoded as in `type_value`.
+    pub fn capability(
+        path: Argument<Value>,
+        address: &str,
+        borrow_type: &str,
+    ) -> Result<Argument<Value>> {
+        Argument::capability_with_kind(path, address, borrow_type, CompositeKind::Resource)
+    }
+    /// Argument from a capability like `capability`, with the borrow type's qualified types encoded as `composite_kind`
+    pub fn capability_with_kind(
+        path: Argument<Value>,
+        address: &str,
+        borrow_type: &str,
+        composite_kind: CompositeKind,
+    ) -> Result<Argument<Value>> {
+        Ok(Argument {
+            r#type: "Capability",
+            value: json!({
+                "path": path,
+                "address": format!("0x{}", hex::encode(decode_address(address)?)),
+                "borrowType": static_type_json(borrow_type, composite_kind, false)?,
+            }),
+        })
+    }
+    /// Argument from a composite value. `id` is the fully qualified type identifier,
+    /// eg. `A.0ae53cb6e3f42a79.Foo.Bar`, and each field value must already be an encoded argument.
+    pub fn composite(kind: CompositeKind, id: &str, fields: Vec<(String, Value)>) -> Argument<Value> {
+        Argument {
+            r#type: kind.as_str(),
+            value: json!({
+                "id": id,
+                "fields": fields
+                    .into_iter()
+                    .map(|(name, value)| json!({"name": name, "value": value}))
+                    .collect::<Vec<Value>>(),
+            }),
+        }
+    }
+    /// Argument from a struct. Shorthand for `Argument::composite(CompositeKind::Struct, ..)`
+    pub fn struct_value(id: &str, fields: Vec<(String, Value)>) -> Argument<Value> {
+        Argument::composite(CompositeKind::Struct, id, fields)
+    }
+}
+/// Boolean arguments
+impl Argument<bool> {
+    pub fn boolean(value: bool) -> Argument<bool> {
+        Argument {
+            r#type: "Bool",
+            value,
+        }
+    }
+}
+/// You can use this to avoid memory allocation when dealing only with str
+impl Argument<&str> {
+    pub fn str(value: &str) -> Argument<&str> {
+        Argument {
+            r#type: "String",
+            value,
+        }
+    }
+    /// Argument from a single grapheme, eg. `"a"` or `"🇨🇦"`. Errors if the value is not exactly one grapheme.
+    pub fn character(value: &str) -> Result<Argument<&str>> {
+        if value.graphemes(true).count() != 1 {
+            bail!("Character must be a single grapheme: {:?}", value);
+        }
+        Ok(Argument {
+            r#type: "Character",
+            value,
+        })
+    }
+}
+/// You will use this for most argument types. Before implementing new types, be sure to read https://docs.onflow.org/cadence/json-cadence-spec
+impl Argument<String> {
+    /// Take a String and turn it into an argument
+    pub fn string(value: String) -> Argument<String> {
+        Argument {
+            r#type: "String",
+            value,
+        }
+    }
+    /// Take a positive f64 and turn it into an argument. Fixed point numbers are encoded as strings, so this will result in additional memory allocation when used.
+    /// Errors if the value is negative, out of range, or has more than 8 decimal places.
+    pub fn ufix64(value: f64) -> Result<Argument<String>> {
+        if value < 0.0 {
+            bail!("UFix64 cannot be negative: {}", value);
+        }
+        Ok(Argument {
+            r#type: "UFix64",
+            value: fixed_point(value, 0, u64::MAX as i128)?,
+        })
+    }
+    /// Take a f64 and turn it into an argument. Fixed point numbers are encoded as strings, so this will result in additional memory allocation when used.
+    /// Errors if the value is out of range or has more than 8 decimal places.
+    pub fn fix64(value: f64) -> Result<Argument<String>> {
+        Ok(Argument {
+            r#type: "Fix64",
+            value: fixed_point(value, i64::MIN as i128, i64::MAX as i128)?,
+        })
+    }
+    /// Take a decimal string, eg. `"184467440737.09551615"`, and turn it into a `UFix64` argument without passing through f64.
+    /// Errors if the value is negative, out of range, or has more than 8 decimal places.
+    pub fn ufix64_str(value: &str) -> Result<Argument<String>> {
+        let scaled = parse_fixed_point(value, 0, u64::MAX as i128)?;
+        Ok(Argument {
+            r#type: "UFix64",
+            value: format_fixed_point(scaled),
+        })
+    }
+    /// Take a decimal string, eg. `"-12.5"`, and turn it into a `Fix64` argument without passing through f64.
+    /// Errors if the value is out of range or has more than 8 decimal places.
+    pub fn fix64_str(value: &str) -> Result<Argument<String>> {
+        let scaled = parse_fixed_point(value, i64::MIN as i128, i64::MAX as i128)?;
+        Ok(Argument {
+            r#type: "Fix64",
+            value: format_fixed_point(scaled),
+        })
+    }
+    /// Take a u8 and turn it into an argument. Integers are encoded as strings, so this will result in additional memory allocation when used.
+    pub fn uint8(value: u8) -> Argument<String> {
+        Argument {
+            r#type: "UInt8",
+            value: value.to_string(),
+        }
+    }
+    /// Take a u64 and turn it into an argument. Integers are encoded as strings, so this will result in additional memory allocation when used.
+    pub fn uint64(value: u64) -> Argument<String> {
+        Argument {
+            r#type: "UInt64",
+            value: value.to_string(),
+        }
+    }
+    /// Take a i64 and turn it into an argument. Integers are encoded as strings, so this will result in additional memory allocation when used.
+    pub fn int64(value: i64) -> Argument<String> {
+        Argument {
+            r#type: "Int64",
+            value: value.to_string(),
+        }
+    }
+    /// Take a decimal string and turn it into a `UInt256` argument, since there is no native u256.
+    /// Errors if the string is not an unsigned decimal integer within range.
+    pub fn uint256(value: &str) -> Result<Argument<String>> {
+        let digits = decimal_digits(value)?;
+        if !fits_decimal(digits, UINT256_MAX) {
+            bail!("UInt256 out of range: {}", value);
+        }
+        Ok(Argument {
+            r#type: "UInt256",
+            value: value.to_owned(),
+        })
+    }
+    /// Take a decimal string, optionally prefixed by `-`, and turn it into an `Int256` argument.
+    /// Errors if the string is not a decimal integer within range.
+    pub fn int256(value: &str) -> Result<Argument<String>> {
+        let (negative, unsigned) = match value.strip_prefix('-') {
+            Some(unsigned) => (true, unsigned),
+            None => (false, value),
+        };
+        let digits = decimal_digits(unsigned)?;
+        let max = if negative { INT256_MIN_ABS } else { INT256_MAX };
+        if !fits_decimal(digits, max) {
+            bail!("Int256 out of range: {}", value);
+        }
+        Ok(Argument {
+            r#type: "Int256",
+            value: value.to_owned(),
+        })
+    }
+    /// Take a hex-encoded string, with or without the `0x` prefix, and turn it into an argument.
+    pub fn address(value: String) -> Argument<String> {
+        Argument {
+            r#type: "Address",
+            value: format!("0x{}", value.trim_start_matches("0x")),
+        }
+    }
+}
+/// Encoding, shared by every argument type
+impl<T: Serialize> Argument<T> {
+    /// Encode the argument as JSON-Cadence bytes
+    pub fn encode(&self) -> Vec<u8> {
+        to_vec(self).expect("arguments always serialize to JSON")
+    }
+    /// The argument as a JSON-Cadence `Value`, for nesting it inside an array, dictionary, optional or composite
+    pub fn to_value(&self) -> Value {
+        serde_json::to_value(self).expect("arguments always serialize to JSON")
+    }
+    #[deprecated(note = "use `encode`, which works for every argument type")]
+    pub fn encode_str(&self) -> Vec<u8> {
+        self.encode()
+    }
+    #[deprecated(note = "use `encode`, which works for every argument type")]
+    pub fn encode_arr(&self) -> Vec<u8> {
+        self.encode()
+    }
+    #[deprecated(note = "use `encode`, which works for every argument type")]
+    pub fn encode_value(&self) -> Vec<u8> {
+        self.encode()
+    }
+}
+/// Anything that can be encoded as a script or transaction argument, ie. any `Argument` or an already built JSON-Cadence `Value`.
+/// Mixed argument types can be passed together as `Vec<Value>` by wrapping each one with `json!`.
+pub trait IntoCadenceArg {
+    /// Encode the argument as JSON-Cadence bytes
+    fn into_cadence_arg(self) -> Vec<u8>;
+}
+impl<T: Serialize> IntoCadenceArg for Argument<T> {
+    fn into_cadence_arg(self) -> Vec<u8> {
+        self.encode()
+    }
+}
+impl IntoCadenceArg for Value {
+    fn into_cadence_arg(self) -> Vec<u8> {
+        to_vec(&self).unwrap()
+    }
+}
+/// Encode each argument, ready for `execute_script` or `build_transaction`
+pub fn encode_arguments<A: IntoCadenceArg>(arguments: Vec<A>) -> Vec<Vec<u8>> {
+    arguments
+        .into_iter()
+        .map(IntoCadenceArg::into_cadence_arg)
+        .collect()
+}
+/// Parse a JSON-Cadence array of arguments, eg. an arguments file written for flow-cli, into encoded arguments
+/// ready for `execute_script` or `build_transaction`. Each argument is checked against the JSON-Cadence spec.
+pub fn parse_arguments(json: &str) -> Result<Vec<Vec<u8>>> {
+    let arguments: Value = serde_json::from_str(json)?;
+    let arguments = match arguments.as_array() {
+        Some(arguments) => arguments,
+        None => bail!("expected a JSON array of arguments, found {}", arguments),
+    };
+    arguments
+        .iter()
+        .enumerate()
+        .map(
+            |(index, argument)| match CadenceValue::from_json(argument) {
+                Ok(_) => Ok(to_vec(argument)?),
+                Err(e) => bail!("invalid argument {}: {}", index, e),
+            },
+        )
+        .collect()
+}
+/// Formats a f64 the way Cadence represents fixed point numbers, with exactly 8 decimal places.
+/// Values with more precision than that are rejected rather than silently rounded.
+/// `min` and `max` bound the value once scaled by 10^8.
+fn fixed_point(value: f64, min: i128, max: i128) -> Result<String> {
+    if !value.is_finite() {
+        bail!("fixed point value out of range: {}", value);
+    }
+    // `Display` for f64 produces the shortest representation that round-trips, without an exponent
+    let repr = value.to_string();
+    let (integer, fraction) = match repr.split_once('.') {
+        Some((integer, fraction)) => (integer, fraction),
+        None => (repr.as_str(), ""),
+    };
+    if fraction.len() > 8 {
+        bail!(
+            "fixed point value {} has more than 8 decimal places and would lose precision",
+            value
+        );
+    }
+    match format!("{}{:0<8}", integer, fraction).parse::<i128>() {
+        Ok(scaled) if scaled >= min && scaled <= max => {}
+        _ => bail!("fixed point value out of range: {}", value),
+    }
+    Ok(format!("{}.{:0<8}", integer, fraction))
+}
+/// Parses a Cadence fixed point decimal, eg. `"-12.5"`, exactly into its value scaled by 10^8.
+/// Errors if it has more than 8 decimal places, or once scaled is outside `min..=max`.
+fn parse_fixed_point(value: &str, min: i128, max: i128) -> Result<i128> {
+    let (negative, digits) = match value.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, value),
+    };
+    let (integer, fraction) = match digits.split_once('.') {
+        Some((integer, fraction)) => (integer, fraction),
+        None => (digits, ""),
+    };
+    if integer.is_empty()
+        || !integer.bytes().all(|b| b.is_ascii_digit())
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        bail!("invalid fixed point value: {}", value);
+    }
+    if fraction.len() > 8 {
+        bail!("fixed point value {} has more than 8 decimal places", value);
+    }
+    let scaled = match format!("{}{:0<8}", integer, fraction).parse::<i128>() {
+        Ok(scaled) if negative => -scaled,
+        Ok(scaled) => scaled,
+        Err(_) => bail!("fixed point value out of range: {}", value),
+    };
+    if scaled < min || scaled > max {
+        bail!("fixed point value out of range: {}", value);
+    }
+    Ok(scaled)
+}
+/// Formats a fixed point value scaled by 10^8 the way Cadence does, with exactly 8 decimal places
+fn format_fixed_point(scaled: i128) -> String {
+    let sign = if scaled < 0 { "-" } else { "" };
+    let abs = scaled.unsigned_abs();
+    format!("{}{}.{:08}", sign, abs / 100_000_000, abs % 100_000_000)
+}
+const UINT256_MAX: &str =
+    "115792089237316195423570985008687907853269984665640564039457584007913129639935";
+const INT256_MAX: &str =
+    "57896044618658097711785492504343953926634992332820282019728792003956564819967";
+const INT256_MIN_ABS: &str =
+    "57896044618658097711785492504343953926634992332820282019728792003956564819968";
+/// Validates an unsigned decimal integer, returning its significant digits
+fn decimal_digits(value: &str) -> Result<&str> {
+    if value.is_empty() || !value.bytes().all(|b| b.is_ascii_digit()) {
+        bail!("not a decimal integer: {:?}", value);
+    }
+    let digits = value.trim_start_matches('0');
+    Ok(if digits.is_empty() { "0" } else { digits })
+}
+/// Compares significant decimal digits against a maximum, without parsing into a native integer
+fn fits_decimal(digits: &str, max: &str) -> bool {
+    digits.len() < max.len() || (digits.len() == max.len() && digits <= max)
+}
+/// Encodes a static type as a JSON-Cadence type.
+/// Qualified identifiers (`A.<address>.<Contract>.<Type>`) are encoded as composites of `composite_kind`,
+/// or as its interfaces when `interface` is set, ie. inside an intersection or restriction.
+fn static_type_json(
+    static_type: &str,
+    composite_kind: CompositeKind,
+    interface: bool,
+) -> Result<Value> {
+    let static_type = static_type.trim();
+    let nested = |static_type| static_type_json(static_type, composite_kind, false);
+    let interfaces = |types: &str| {
+        split_top_level(types, &[','])
+            .into_iter()
+            .map(|x| static_type_json(x, composite_kind, true))
+            .collect::<Result<Vec<Value>>>()
+    };
+    if let Some(inner) = static_type.strip_suffix('?') {
+        return Ok(json!({"kind": "Optional", "type": nested(inner)?}));
+    }
+    // Cadence 1.0 entitlements, eg. `auth(Withdraw) &Vault`
+    if let Some(rest) = static_type.strip_prefix("auth(") {
+        let (entitlements, referenced) = match rest.split_once(')') {
+            Some((entitlements, referenced)) => (entitlements, referenced.trim_start()),
+            None => bail!("unclosed entitlements in {:?}", static_type),
+        };
+        let referenced = match referenced.strip_prefix('&') {
+            Some(referenced) => referenced,
+            None => bail!(
+                "expected a reference after the entitlements in {:?}",
+                static_type
+            ),
+        };
+        let kind = if entitlements.contains('|') {
+            "EntitlementDisjunctionSet"
+        } else {
+            "EntitlementConjunctionSet"
+        };
+        let entitlements: Vec<Value> = split_top_level(entitlements, &[',', '|'])
+            .into_iter()
+            .map(|x| json!({"kind": "Entitlement", "typeID": x}))
+            .collect();
+        return Ok(json!({
+            "kind": "Reference",
+            "authorization": {"kind": kind, "entitlements": entitlements},
+            "type": nested(referenced)?,
+        }));
+    }
+    // pre-1.0 authorized references, eg. `auth &Vault`
+    if let Some(referenced) = static_type.strip_prefix("auth &") {
+        return Ok(json!({"kind": "Reference", "authorized": true, "type": nested(referenced)?}));
+    }
+    if let Some(referenced) = static_type.strip_prefix('&') {
+        // `authorized` is read by pre-1.0 nodes and `authorization` by Cadence 1.0 ones
+        return Ok(json!({
+            "kind": "Reference",
+            "authorized": false,
+            "authorization": {"kind": "Unauthorized", "entitlements": null},
+            "type": nested(referenced)?,
+        }));
+    }
+    if let Some(inner) = static_type
+        .strip_prefix("Capability<")
+        .and_then(|x| x.strip_suffix('>'))
+    {
+        return Ok(json!({"kind": "Capability", "type": nested(inner)?}));
+    }
+    if let Some(inner) = static_type
+        .strip_prefix('[')
+        .and_then(|x| x.strip_suffix(']'))
+    {
+        return match split_top_level(inner, &[';'])[..] {
+            [element] => Ok(json!({"kind": "VariableSizedArray", "type": nested(element)?})),
+            [element, size] => Ok(json!({
+                "kind": "ConstantSizedArray",
+                "type": nested(element)?,
+                "size": size.parse::<u64>()?,
+            })),
+            _ => bail!("invalid array type {:?}", static_type),
+        };
+    }
+    if static_type.ends_with('}') {
+        // the brace matching the closing one, scanning backwards
+        let mut depth = 0;
+        let open = static_type.char_indices().rev().find(|(_, c)| {
+            match c {
+                ')' | ']' | '}' | '>' => depth += 1,
+                '(' | '[' | '{' | '<' => depth -= 1,
+                _ => {}
             }
+            depth == 0
+        });
+        let open = match open {
+            Some((open, '{')) => open,
+            _ => bail!("unbalanced braces in {:?}", static_type),
+        };
+        let inner = &static_type[open + 1..static_type.len() - 1];
+        if open > 0 {
+            // pre-1.0 restricted types, eg. `Vault{Receiver}`
+            return Ok(json!({
+                "kind": "Restriction",
+                "typeID": "",
+                "type": nested(&static_type[..open])?,
+                "restrictions": interfaces(inner)?,
+            }));
         }
-        ";
-        let latest_block: BlockResponse = self.get_block(None, None, Some(false)).await?;
-        let account: flow::Account = self.get_account(payer).await?.account.unwrap();
-        let proposer = TransactionProposalKey {
-            address: hex::decode(payer).unwrap(),
-            key_id,
-            sequence_number: account.keys[key_id as usize].sequence_number as u64,
+        return match split_top_level(inner, &[':'])[..] {
+            [key, value] => {
+                Ok(json!({"kind": "Dictionary", "key": nested(key)?, "value": nested(value)?}))
+            }
+            // Cadence 1.0 intersection types, eg. `{Receiver, Balance}`
+            [_] => Ok(json!({"kind": "Intersection", "typeID": "", "types": interfaces(inner)?})),
+            _ => bail!("invalid dictionary type {:?}", static_type),
+        };
+    }
+    if static_type.starts_with("A.") {
+        let kind = match (composite_kind, interface) {
+            (CompositeKind::Resource, true) => "ResourceInterface",
+            (_, true) => "StructInterface",
+            (kind, false) => kind.as_str(),
+        };
+        return Ok(
+            json!({"kind": kind, "typeID": static_type, "fields": [], "initializers": [], "type": ""}),
+        );
+    }
+    if static_type.is_empty() || !static_type.chars().all(|c| c.is_alphanumeric() || c == '_') {
+        bail!("unsupported static type {:?}", static_type);
+    }
+    Ok(json!({ "kind": static_type }))
+}
+/// Splits a type list at the separators which are not nested in brackets, trimming each part
+fn split_top_level<'a>(types: &'a str, separators: &[char]) -> Vec<&'a str> {
+    let mut parts = vec![];
+    let mut depth = 0;
+    let mut start = 0;
+    for (index, c) in types.char_indices() {
+        match c {
+            '(' | '[' | '{' | '<' => depth += 1,
+            ')' | ']' | '}' | '>' => depth -= 1,
+            _ if depth == 0 && separators.contains(&c) => {
+                parts.push(types[start..index].trim());
+                start = index + c.len_utf8();
+            }
+            _ => {}
+        }
+    }
+    parts.push(types[start..].trim());
+    parts
+}
+/// Utility function. Right-pads `vec` with zeros up to `count` bytes.
+/// Errors if `vec` is already longer than `count`, eg. an address that still has its `0x` prefix.
+fn padding(vec: &mut Vec<u8>, count: usize) -> Result<()> {
+    if vec.len() > count {
+        bail!("expected at most {} bytes, found {}", count, vec.len());
+    }
+    vec.resize(count, 0);
+    Ok(())
+}
+/// A Flow chain, which decides the addresses of the core contracts
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    Emulator,
+}
+impl Network {
+    /// The constant the chain's addresses are offset from the code words by
+    fn address_code_word(&self) -> u64 {
+        match self {
+            Network::Mainnet => 0,
+            Network::Testnet => 0x6834ba37b3980209,
+            Network::Emulator => 0x1cb159857af02018,
+        }
+    }
+    /// The address of the `FungibleToken` contract
+    pub fn fungible_token_address(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "0xf233dcee88fe0abe",
+            Network::Testnet => "0x9a0766d93b6608b7",
+            Network::Emulator => "0xee82856bf20e2aa6",
+        }
+    }
+    /// The address of the `FlowToken` contract
+    pub fn flow_token_address(&self) -> &'static str {
+        match self {
+            Network::Mainnet => "0x1654653399040a61",
+            Network::Testnet => "0x7e60df042a9c0868",
+            Network::Emulator => "0x0ae53cb6e3f42a79",
+        }
+    }
+}
+/// The standard FLOW transfer transaction, with the contract addresses left as placeholders
+pub const TRANSFER_FLOW_TEMPLATE: &str = "
+import FungibleToken from 0xFUNGIBLETOKENADDRESS
+import FlowToken from 0xFLOWTOKENADDRESS
+
+transaction(amount: UFix64, to: Address) {
+    let sentVault: @FungibleToken.Vault
+
+    prepare(signer: AuthAccount) {
+        let vaultRef = signer.borrow<&FlowToken.Vault>(from: /storage/flowTokenVault)
+            ?? panic(\"Could not borrow reference to the owner's Vault!\")
+        self.sentVault <- vaultRef.withdraw(amount: amount)
+    }
+
+    execute {
+        let receiverRef = getAccount(to)
+            .getCapability(/public/flowTokenReceiver)
+            .borrow<&{FungibleToken.Receiver}>()
+            ?? panic(\"Could not borrow receiver reference to the recipient's Vault\")
+        receiverRef.deposit(from: <-self.sentVault)
+    }
+}
+";
+/// The standard FLOW transfer transaction for Cadence 1.0, with the contract addresses left as placeholders
+pub const TRANSFER_FLOW_TEMPLATE_CADENCE_1: &str = "
+import FungibleToken from 0xFUNGIBLETOKENADDRESS
+import FlowToken from 0xFLOWTOKENADDRESS
+
+transaction(amount: UFix64, to: Address) {
+    let sentVault: @{FungibleToken.Vault}
+
+    prepare(signer: auth(BorrowValue) &Account) {
+        let vaultRef = signer.storage.borrow<auth(FungibleToken.Withdraw) &FlowToken.Vault>(from: /storage/flowTokenVault)
+            ?? panic(\"Could not borrow reference to the owner's Vault!\")
+        self.sentVault <- vaultRef.withdraw(amount: amount)
+    }
+
+    execute {
+        let receiverRef = getAccount(to)
+            .capabilities
+            .borrow<&{FungibleToken.Receiver}>(/public/flowTokenReceiver)
+            ?? panic(\"Could not borrow receiver reference to the recipient's Vault\")
+        receiverRef.deposit(from: <-self.sentVault)
+    }
+}
+";
+/// Cadence 1.0 removed `removePublicKey`, so both `remove_key` and `revoke_key` use `keys.revoke`
+const CADENCE_1_REVOKE_KEY: &str = "
+transaction(keyIndex: UInt64) {
+    prepare(signer: auth(RevokeKey) &Account) {
+        signer.keys.revoke(keyIndex: Int(keyIndex))
+            ?? panic(\"No key with the given index\")
+    }
+}
+";
+/// The Cadence transactions sent by the `FlowConnection` helpers.
+/// The built-in templates are borrowed; owned ones can be loaded at runtime, eg. `Cow::Owned(std::fs::read_to_string(path)?)`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Templates {
+    /// Takes the arguments built by `PublicKeyArgs::array_arguments`
+    pub create_account: Cow<'static, str>,
+    /// Takes `amount: UFix64, to: Address`, with the core contract addresses as placeholders
+    pub transfer_flow: Cow<'static, str>,
+    /// Takes `publicKey: String`
+    pub add_key: Cow<'static, str>,
+    /// Takes the arguments built by `PublicKeyArgs`
+    pub add_public_key: Cow<'static, str>,
+    /// Takes `keyIndex: UInt64`
+    pub remove_key: Cow<'static, str>,
+    /// Takes `keyIndex: UInt64`
+    pub revoke_key: Cow<'static, str>,
+    /// Takes `name: String, code: String`, with the code hex-encoded
+    pub add_contract: Cow<'static, str>,
+    /// Takes `name: String, code: String`, with the code hex-encoded
+    pub update_contract: Cow<'static, str>,
+    /// Takes `name: String`
+    pub remove_contract: Cow<'static, str>,
+}
+/// The Cadence language version the network runs, which decides the templates the helpers send
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CadenceVersion {
+    /// Cadence before 1.0, using `AuthAccount`
+    Legacy,
+    /// Cadence 1.0, using entitlements on `&Account`.
+    /// `add_key` takes a raw 64 byte public key, added as an `ECDSA_P256`/`SHA3_256` key with full weight.
+    Cadence1,
+}
+impl CadenceVersion {
+    /// The built-in templates for this version
+    pub fn templates(&self) -> Templates {
+        match self {
+            CadenceVersion::Legacy => Templates {
+                create_account: Cow::Borrowed("
+                transaction(publicKeys: [[UInt8]], signatureAlgorithms: [UInt8], hashAlgorithms: [UInt8], weights: [UFix64]) {
+                    prepare(signer: AuthAccount) {
+                        let acct = AuthAccount(payer: signer)
+
+                        var i = 0
+                        while i < publicKeys.length {
+                            acct.keys.add(
+                                publicKey: PublicKey(
+                                    publicKey: publicKeys[i],
+                                    signatureAlgorithm: SignatureAlgorithm(rawValue: signatureAlgorithms[i])!
+                                ),
+                                hashAlgorithm: HashAlgorithm(rawValue: hashAlgorithms[i])!,
+                                weight: weights[i]
+                            )
+                            i = i + 1
+                        }
+                    }
+                }
+                "),
+                transfer_flow: Cow::Borrowed(TRANSFER_FLOW_TEMPLATE),
+                add_key: Cow::Borrowed("
+                transaction(publicKey: String) {
+                    prepare(signer: AuthAccount) {
+                        signer.addPublicKey(publicKey.decodeHex())
+                    }
+                }
+                "),
+                add_public_key: Cow::Borrowed("
+                transaction(publicKey: [UInt8], signatureAlgorithm: UInt8, hashAlgorithm: UInt8, weight: UFix64) {
+                    prepare(signer: AuthAccount) {
+                        signer.keys.add(
+                            publicKey: PublicKey(
+                                publicKey: publicKey,
+                                signatureAlgorithm: SignatureAlgorithm(rawValue: signatureAlgorithm)!
+                            ),
+                            hashAlgorithm: HashAlgorithm(rawValue: hashAlgorithm)!,
+                            weight: weight
+                        )
+                    }
+                }
+                "),
+                remove_key: Cow::Borrowed("
+                transaction(keyIndex: UInt64) {
+                    prepare(signer: AuthAccount) {
+                        signer.removePublicKey(Int(keyIndex))
+                    }
+                }
+                "),
+                revoke_key: Cow::Borrowed("
+                transaction(keyIndex: UInt64) {
+                    prepare(signer: AuthAccount) {
+                        signer.keys.revoke(keyIndex: Int(keyIndex))
+                            ?? panic(\"No key with the given index\")
+                    }
+                }
+                "),
+                add_contract: Cow::Borrowed("
+                transaction(name: String, code: String) {
+                    prepare(signer: AuthAccount) {
+                        signer.contracts.add(name: name, code: code.decodeHex())
+                    }
+                }
+                "),
+                update_contract: Cow::Borrowed("
+                transaction(name: String, code: String) {
+                    prepare(signer: AuthAccount) {
+                        signer.contracts.update__experimental(name: name, code: code.decodeHex())
+                    }
+                }
+                "),
+                remove_contract: Cow::Borrowed("
+                transaction(name: String) {
+                    prepare(signer: AuthAccount) {
+                        signer.contracts.remove(name: name)
+                    }
+                }
+                "),
+            },
+            CadenceVersion::Cadence1 => Templates {
+                create_account: Cow::Borrowed("
+                transaction(publicKeys: [[UInt8]], signatureAlgorithms: [UInt8], hashAlgorithms: [UInt8], weights: [UFix64]) {
+                    prepare(signer: auth(BorrowValue) &Account) {
+                        let acct = Account(payer: signer)
+
+                        var i = 0
+                        while i < publicKeys.length {
+                            acct.keys.add(
+                                publicKey: PublicKey(
+                                    publicKey: publicKeys[i],
+                                    signatureAlgorithm: SignatureAlgorithm(rawValue: signatureAlgorithms[i])!
+                                ),
+                                hashAlgorithm: HashAlgorithm(rawValue: hashAlgorithms[i])!,
+                                weight: weights[i]
+                            )
+                            i = i + 1
+                        }
+                    }
+                }
+                "),
+                transfer_flow: Cow::Borrowed(TRANSFER_FLOW_TEMPLATE_CADENCE_1),
+                add_key: Cow::Borrowed("
+                transaction(publicKey: String) {
+                    prepare(signer: auth(AddKey) &Account) {
+                        signer.keys.add(
+                            publicKey: PublicKey(
+                                publicKey: publicKey.decodeHex(),
+                                signatureAlgorithm: SignatureAlgorithm.ECDSA_P256
+                            ),
+                            hashAlgorithm: HashAlgorithm.SHA3_256,
+                            weight: 1000.0
+                        )
+                    }
+                }
+                "),
+                add_public_key: Cow::Borrowed("
+                transaction(publicKey: [UInt8], signatureAlgorithm: UInt8, hashAlgorithm: UInt8, weight: UFix64) {
+                    prepare(signer: auth(AddKey) &Account) {
+                        signer.keys.add(
+                            publicKey: PublicKey(
+                                publicKey: publicKey,
+                                signatureAlgorithm: SignatureAlgorithm(rawValue: signatureAlgorithm)!
+                            ),
+                            hashAlgorithm: HashAlgorithm(rawValue: hashAlgorithm)!,
+                            weight: weight
+                        )
+                    }
+                }
+                "),
+                remove_key: Cow::Borrowed(CADENCE_1_REVOKE_KEY),
+                revoke_key: Cow::Borrowed(CADENCE_1_REVOKE_KEY),
+                add_contract: Cow::Borrowed("
+                transaction(name: String, code: String) {
+                    prepare(signer: auth(AddContract) &Account) {
+                        signer.contracts.add(name: name, code: code.decodeHex())
+                    }
+                }
+                "),
+                update_contract: Cow::Borrowed("
+                transaction(name: String, code: String) {
+                    prepare(signer: auth(UpdateContract) &Account) {
+                        signer.contracts.update(name: name, code: code.decodeHex())
+                    }
+                }
+                "),
+                remove_contract: Cow::Borrowed("
+                transaction(name: String) {
+                    prepare(signer: auth(RemoveContract) &Account) {
+                        signer.contracts.remove(name: name)
+                    }
+                }
+                "),
+            },
+        }
+    }
+}
+/// Decodes a hex-encoded Flow address, with or without the `0x` prefix, into its 8 bytes.
+/// Shorter addresses are left-padded with zeros, eg. `0x1` is `0x0000000000000001`.
+pub fn decode_address(address: &str) -> Result<Vec<u8>> {
+    let mut digits = address.trim_start_matches("0x").to_owned();
+    if digits.len() % 2 == 1 {
+        digits.insert(0, '0');
+    }
+    let bytes = match hex::decode(&digits) {
+        Ok(bytes) => bytes,
+        Err(e) => bail!("Invalid address {}: {}", address, e),
+    };
+    if bytes.len() > 8 {
+        bail!("Invalid address {}: longer than 8 bytes", address);
+    }
+    let mut padded = vec![0; 8 - bytes.len()];
+    padded.extend(bytes);
+    Ok(padded)
+}
+#[cfg(feature = "transport")]
+/// The hex-encoded address of the account announced by the last `flow.AccountCreated` event, without `0x`
+fn created_account_address(events: &[flow::Event]) -> Result<String> {
+    let event = match events
+        .iter()
+        .rev()
+        .find(|x| x.r#type == "flow.AccountCreated")
+    {
+        Some(event) => event,
+        None => bail!("No flow.AccountCreated event was emitted"),
+    };
+    let address = match CadenceValue::decode(&event.payload)? {
+        CadenceValue::Composite { fields, .. } => fields
+            .into_iter()
+            .find(|(name, _)| name == "address")
+            .map(|(_, value)| value),
+        _ => None,
+    };
+    match address {
+        Some(CadenceValue::Address(address)) => Ok(hex::encode(decode_address(&address)?)),
+        _ => bail!("The flow.AccountCreated event has no address field"),
+    }
+}
+/// Decodes a hex-encoded block id, with or without `0x`
+fn decode_block_id(block_id: &str) -> Result<Vec<u8>> {
+    match hex::decode(block_id.trim_start_matches("0x")) {
+        Ok(block_id) => Ok(block_id),
+        Err(e) => bail!("Invalid block id {}: {}", block_id, e),
+    }
+}
+/// The columns of the parity-check matrix of the linear code Flow generates account addresses from
+const ADDRESS_PARITY_CHECK_COLUMNS: [u32; 64] = [
+    0x00001, 0x00002, 0x00004, 0x00008, 0x00010, 0x00020, 0x00040, 0x00080, 0x00100, 0x00200,
+    0x00400, 0x00800, 0x01000, 0x02000, 0x04000, 0x08000, 0x10000, 0x20000, 0x40000, 0x7328d,
+    0x6689a, 0x6112f, 0x6084b, 0x433fd, 0x42aab, 0x41951, 0x233ce, 0x22a81, 0x21948, 0x1ef60,
+    0x1deca, 0x1c639, 0x1bdd8, 0x1a535, 0x194ac, 0x18c46, 0x1632b, 0x1529b, 0x14a43, 0x13184,
+    0x12942, 0x118c1, 0x0f812, 0x0e027, 0x0d00e, 0x0c83c, 0x0b01d, 0x0a831, 0x0982b, 0x07034,
+    0x0682a, 0x05819, 0x03807, 0x007d2, 0x00727, 0x0068e, 0x0067c, 0x0059d, 0x004eb, 0x003b4,
+    0x0036a, 0x002d9, 0x001c7, 0x0003f,
+];
+/// Whether the hex-encoded address (0x allowed) is a valid account address on the chain, by Flow's address checksum.
+/// Addresses are code words of a linear code, offset per chain, so a mistyped address, or one from another chain,
+/// is almost always rejected. Check addresses entered by users before sending funds to them.
+pub fn is_valid_address(address: &str, network: Network) -> bool {
+    let address = match decode_address(address) {
+        Ok(address) => address,
+        Err(_) => return false,
+    };
+    let mut code_word = address
+        .iter()
+        .fold(0u64, |word, byte| word << 8 | *byte as u64)
+        ^ network.address_code_word();
+    // the zero code word is not an account
+    if code_word == 0 {
+        return false;
+    }
+    let mut parity = 0;
+    for column in ADDRESS_PARITY_CHECK_COLUMNS.iter() {
+        if code_word & 1 == 1 {
+            parity ^= column;
+        }
+        code_word >>= 1;
+    }
+    parity == 0
+}
+/// The domain tag prepended to transaction payloads and envelopes before signing: `FLOW-V0.0-transaction`, right-padded with zeros to 32 bytes
+pub const TRANSACTION_DOMAIN_TAG: [u8; 32] = *b"FLOW-V0.0-transaction\0\0\0\0\0\0\0\0\0\0\0";
+/// A domain tag for chains which do not use Flow's, eg. `domain_tag("MYCHAIN-V0.0-transaction")`: the tag right-padded with zeros to 32 bytes
+pub fn domain_tag(tag: &str) -> Result<[u8; 32]> {
+    if tag.len() > 32 {
+        bail!("Domain tag {} is longer than 32 bytes", tag);
+    }
+    let mut padded = [0; 32];
+    padded[..tag.len()].copy_from_slice(tag.as_bytes());
+    Ok(padded)
+}
+/// An ECDSA_P256 private key, either hex-encoded or as its raw 32 bytes.
+/// The key is zeroed when dropped.
+#[derive(Clone)]
+pub enum PrivateKey {
+    Hex(String),
+    Bytes(Vec<u8>),
+}
+impl PrivateKey {
+    /// Calls `f` with the raw key, decoding it first if it is hex-encoded
+    fn with_bytes<R>(&self, f: impl FnOnce(&[u8]) -> Result<R>) -> Result<R> {
+        match self {
+            PrivateKey::Hex(key) => f(&Zeroizing::new(hex::decode(key)?)),
+            PrivateKey::Bytes(key) => f(key),
+        }
+    }
+}
+impl From<String> for PrivateKey {
+    fn from(key: String) -> Self {
+        PrivateKey::Hex(key)
+    }
+}
+impl From<&str> for PrivateKey {
+    fn from(key: &str) -> Self {
+        PrivateKey::Hex(key.to_owned())
+    }
+}
+impl From<Vec<u8>> for PrivateKey {
+    fn from(key: Vec<u8>) -> Self {
+        PrivateKey::Bytes(key)
+    }
+}
+impl Drop for PrivateKey {
+    fn drop(&mut self) {
+        match self {
+            PrivateKey::Hex(key) => key.zeroize(),
+            PrivateKey::Bytes(key) => key.zeroize(),
+        }
+    }
+}
+impl ZeroizeOnDrop for PrivateKey {}
+/// The Access API of a local flow-emulator, on its default port
+pub const EMULATOR_ACCESS_API: &str = "http://localhost:3569";
+/// The address of the flow-emulator's service account
+pub const EMULATOR_SERVICE_ACCOUNT: &str = "f8d6e0586b0a20c7";
+/// The hex-encoded private key of the flow-emulator's service account when it is started without a configured key.
+/// Emulators started from a `flow.json` use the key in that file instead. Never use this key outside local testing.
+pub const EMULATOR_SERVICE_KEY: &str =
+    "f8e188e8af0b8b414be59c4a1a15cc666c898fb34d94156e9b51e18bfde754a5";
+/// Construct a signature object. Pass this into the payload
+/// or envelope signatures when signing a transaction.
+/// The private key is zeroed when the `Sign` is dropped.
+pub struct Sign {
+    pub address: String,
+    pub key_id: u32,
+    pub private_key: PrivateKey,
+}
+impl Sign {
+    /// A signer with a hex-encoded private key
+    pub fn new(address: &str, key_id: u32, private_key: &str) -> Self {
+        Sign {
+            address: address.to_owned(),
+            key_id,
+            private_key: private_key.into(),
+        }
+    }
+    /// A signer with the raw 32 byte private key, eg. as exported from a KMS, which is used without a hex round trip
+    pub fn from_bytes(address: &str, key_id: u32, private_key: Vec<u8>) -> Self {
+        Sign {
+            address: address.to_owned(),
+            key_id,
+            private_key: private_key.into(),
+        }
+    }
+    /// The flow-emulator's service account key 0, signing with `EMULATOR_SERVICE_KEY`. For local testing only.
+    pub fn emulator_service_account() -> Self {
+        Sign::new(EMULATOR_SERVICE_ACCOUNT, 0, EMULATOR_SERVICE_KEY)
+    }
+}
+impl ZeroizeOnDrop for Sign {}
+/// build_transaction will construct a `flow::Transaction` with the provided script and arguments.
+/// See the `Argument` struct for details on how to construct arguments.
+/// The reference block id must be the raw 32 byte id, and the proposer's address the raw 8 byte address.
+pub async fn build_transaction(
+    script: Vec<u8>,
+    arguments: Vec<Vec<u8>>,
+    reference_block_id: Vec<u8>,
+    gas_limit: u64,
+    proposer: TransactionProposalKey,
+    authorizers: Vec<String>,
+    payer: String,
+) -> Result<Transaction> {
+    if reference_block_id.len() != 32 {
+        bail!(
+            "Invalid reference block id: expected 32 bytes, found {}",
+            reference_block_id.len()
+        );
+    }
+    if proposer.address.len() != 8 {
+        bail!(
+            "Invalid proposer address: expected 8 bytes, found {}",
+            proposer.address.len()
+        );
+    }
+    Ok(Transaction {
+        script,
+        arguments,
+        reference_block_id,
+        gas_limit,
+        proposal_key: Some(proposer),
+        authorizers: authorizers
+            .iter()
+            .map(|x| decode_address(x))
+            .collect::<Result<_>>()?,
+        payload_signatures: vec![],
+        envelope_signatures: vec![],
+        payer: decode_address(&payer)?,
+    })
+}
+/// Provides an envelope of the given transaction
+fn envelope_from_transaction(
+    transaction: &Transaction,
+    payload_signatures: &[TransactionSignature],
+) -> Result<Vec<u8>> {
+    let mut stream = RlpStream::new_list(2);
+    stream.append_raw(&payload_from_transaction(transaction)?, 1);
+    append_signatures(&mut stream, transaction, payload_signatures)?;
+    Ok(stream.out().to_vec())
+}
+/// Provides a payload from a transaction
+fn payload_from_transaction(transaction: &Transaction) -> Result<Vec<u8>> {
+    let proposal_key = transaction
+        .proposal_key
+        .as_ref()
+        .ok_or_else(|| anyhow::anyhow!("transaction has no proposal key"))?;
+    let mut proposal_address = proposal_key.address.clone();
+    padding(&mut proposal_address, 8)?;
+    let mut ref_block = transaction.reference_block_id.clone();
+    padding(&mut ref_block, 32)?;
+
+    let mut stream = RlpStream::new_list(9);
+    stream.append(&transaction.script);
+    stream.begin_list(transaction.arguments.len());
+    for arg in &transaction.arguments {
+        stream.append(arg);
+    }
+
+    stream.append(&ref_block);
+    stream.append(&transaction.gas_limit);
+    stream.append(&proposal_address);
+    stream.append(&proposal_key.key_id);
+    stream.append(&proposal_key.sequence_number);
+    stream.append(&transaction.payer);
+
+    stream.begin_list(transaction.authorizers.len());
+    for auth in &transaction.authorizers {
+        stream.append(auth);
+    }
+    Ok(stream.out().to_vec())
+}
+/// The position of `address` in the transaction's signer list: the proposer, then the payer, then the authorizers, without duplicates.
+/// This is the signer index a signature by `address` is encoded with.
+pub fn signer_index(transaction: &Transaction, address: &[u8]) -> Option<usize> {
+    let mut signers: Vec<&[u8]> = vec![];
+    let proposer = transaction.proposal_key.as_ref().map(|key| key.address.as_slice());
+    for signer in proposer
+        .into_iter()
+        .chain(std::iter::once(transaction.payer.as_slice()))
+        .chain(transaction.authorizers.iter().map(|x| x.as_slice()))
+    {
+        if !signers.contains(&signer) {
+            signers.push(signer);
+        }
+    }
+    signers.iter().position(|signer| *signer == address)
+}
+/// Appends the canonical form of a list of signatures: `[signer index, key id, signature]` each.
+/// Errors for a signature by an address which is not the proposer, the payer or an authorizer.
+fn append_signatures(
+    stream: &mut RlpStream,
+    transaction: &Transaction,
+    signatures: &[TransactionSignature],
+) -> Result<()> {
+    stream.begin_list(signatures.len());
+    for sig in signatures {
+        let index = match signer_index(transaction, &sig.address) {
+            Some(index) => index,
+            None => bail!(
+                "the signature by 0x{} key {} is not by a signer of the transaction",
+                hex::encode(&sig.address),
+                sig.key_id
+            ),
+        };
+        stream.begin_list(3);
+        stream.append(&(index as u32));
+        stream.append(&sig.key_id);
+        stream.append(&sig.signature);
+    }
+    Ok(())
+}
+/// The canonical encoding of a signed transaction: `rlp([payload, payload_signatures, envelope_signatures])`
+fn canonical_encoding(transaction: &Transaction) -> Result<Vec<u8>> {
+    let payload = payload_from_transaction(transaction)?;
+    let mut stream = RlpStream::new_list(3);
+    stream.append_raw(&payload, 1);
+    append_signatures(&mut stream, transaction, &transaction.payload_signatures)?;
+    append_signatures(&mut stream, transaction, &transaction.envelope_signatures)?;
+    Ok(stream.out().to_vec())
+}
+/// Computes the id of a signed transaction: the SHA3-256 hash of its canonical encoding.
+/// This is the same id the node returns from `send_transaction`, so it can be known before submitting.
+pub fn transaction_id(transaction: &Transaction) -> Result<Vec<u8>> {
+    Ok(Sha3_256::digest(&canonical_encoding(transaction)?).to_vec())
+}
+/// The length in bytes of the transaction's canonical encoding, including any signatures it already has
+pub fn transaction_byte_size(transaction: &Transaction) -> Result<usize> {
+    Ok(canonical_encoding(transaction)?.len())
+}
+/// The fee parameters of the `FlowFees` contract, as returned by `FlowFees.getFeeParameters()`.
+/// They are set by the service account and change over time, so read them from the chain rather than hardcoding them.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct FeeParameters {
+    /// Multiplies the whole fee while the network is congested
+    pub surge_factor: f64,
+    /// FLOW per unit of inclusion effort
+    pub inclusion_effort_cost: f64,
+    /// FLOW per unit of execution effort
+    pub execution_effort_cost: f64,
+}
+/// The most the transaction can be charged, in FLOW: `surge_factor * (inclusion_effort_cost + gas_limit * execution_effort_cost)`.
+/// The inclusion effort of a transaction is currently a flat 1.0 regardless of its size, and the execution effort is at most its gas limit;
+/// the actual fee is usually much lower, as it is charged for the computation used.
+pub fn estimate_fees(transaction: &Transaction, params: &FeeParameters) -> f64 {
+    params.surge_factor
+        * (params.inclusion_effort_cost
+            + transaction.gas_limit as f64 * params.execution_effort_cost)
+}
+/// The ECDSA_P256 implementation behind `sign` and `verify`, so the curve arithmetic can be swapped without touching the signing code.
+/// Messages are hashed with SHA3_256.
+trait EcdsaBackend {
+    /// Signs `message` with the 32 byte secret key, returning the 64 byte `r || s` signature
+    fn sign(message: &[u8], secret_key: &[u8]) -> Result<Vec<u8>>;
+    /// Whether `signature` over `message` was produced by the key, given as an uncompressed SEC1 point
+    fn verify(message: &[u8], signature: &[u8], public_key: &[u8]) -> Result<bool>;
+}
+/// The default backend, `p256_flow`
+#[cfg(not(feature = "p256-upstream"))]
+struct P256Flow;
+#[cfg(not(feature = "p256-upstream"))]
+impl EcdsaBackend for P256Flow {
+    fn sign(message: &[u8], secret_key: &[u8]) -> Result<Vec<u8>> {
+        let sig_key = SigningKey::from(SecretKey::from_be_bytes(secret_key)?);
+        let signature = sig_key.sign(message);
+        Ok(signature.as_bytes().to_vec())
+    }
+    fn verify(message: &[u8], signature: &[u8], public_key: &[u8]) -> Result<bool> {
+        let verifying_key = VerifyingKey::from_sec1_bytes(public_key)?;
+        let signature = Signature::from_bytes(signature)?;
+        Ok(verifying_key.verify(message, &signature).is_ok())
+    }
+}
+/// RustCrypto's upstream `p256`, selected by the `p256-upstream` feature
+#[cfg(feature = "p256-upstream")]
+struct P256Upstream;
+#[cfg(feature = "p256-upstream")]
+impl EcdsaBackend for P256Upstream {
+    fn sign(message: &[u8], secret_key: &[u8]) -> Result<Vec<u8>> {
+        use p256_upstream::ecdsa::signature::DigestSigner;
+        let sig_key = p256_upstream::ecdsa::SigningKey::from_bytes(secret_key)?;
+        let signature: p256_upstream::ecdsa::Signature =
+            sig_key.sign_digest(Sha3_256::new().chain(message));
+        Ok(signature.as_ref().to_vec())
+    }
+    fn verify(message: &[u8], signature: &[u8], public_key: &[u8]) -> Result<bool> {
+        use p256_upstream::ecdsa::signature::{DigestVerifier, Signature as _};
+        let verifying_key = p256_upstream::ecdsa::VerifyingKey::from_sec1_bytes(public_key)?;
+        let signature = p256_upstream::ecdsa::Signature::from_bytes(signature)?;
+        Ok(verifying_key
+            .verify_digest(Sha3_256::new().chain(message), &signature)
+            .is_ok())
+    }
+}
+#[cfg(not(feature = "p256-upstream"))]
+type Backend = P256Flow;
+#[cfg(feature = "p256-upstream")]
+type Backend = P256Upstream;
+/// Returns the provided message as bytes, signed by the private key.
+fn sign(message: &[u8], private_key: &PrivateKey) -> Result<Vec<u8>> {
+    private_key.with_bytes(|secret_key| Backend::sign(message, secret_key))
+}
+/// Returns whether `signature` over the message was produced by the hex-encoded, 64 byte public key.
+fn verify(message: &[u8], signature: &[u8], public_key: &str) -> Result<bool> {
+    let public_key = [&[0x04], &hex::decode(public_key)?[..]].concat();
+    Backend::verify(message, signature, &public_key)
+}
+/// The domain tag prepended to user messages, such as FCL account proofs: `FLOW-V0.0-user`, right-padded with zeros to 32 bytes
+pub const USER_DOMAIN_TAG: [u8; 32] = *b"FLOW-V0.0-user\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0\0";
+/// The message signed for an FCL account proof: the user domain tag followed by `rlp([app_id, address, nonce])`.
+/// The nonce is hex-encoded, as provided by the app.
+fn account_proof_message(app_id: &str, address: &str, nonce: &str) -> Result<Vec<u8>> {
+    let address = decode_address(address)?;
+    let mut stream = RlpStream::new_list(3);
+    stream.append(&app_id.as_bytes());
+    stream.append(&address);
+    stream.append(&hex::decode(nonce)?);
+    Ok([&USER_DOMAIN_TAG[..], &stream.out()].concat())
+}
+/// Sign an FCL account proof, proving to the app identified by `app_id` that the signer controls `address`
+pub fn sign_account_proof(
+    app_id: &str,
+    address: &str,
+    nonce: &str,
+    signer: &Sign,
+) -> Result<Vec<u8>> {
+    sign(
+        &account_proof_message(app_id, address, nonce)?,
+        &signer.private_key,
+    )
+}
+/// Verify an FCL account proof signature against one of the account's hex-encoded public keys.
+/// The app should also check that the key belongs to `address`, is not revoked, and carries enough weight.
+pub fn verify_account_proof(
+    app_id: &str,
+    address: &str,
+    nonce: &str,
+    signature: &[u8],
+    public_key: &str,
+) -> Result<bool> {
+    verify(
+        &account_proof_message(app_id, address, nonce)?,
+        signature,
+        public_key,
+    )
+}
+/// Verify every payload and envelope signature of the transaction against the signing accounts' keys,
+/// eg. before submitting a transaction assembled from several parties' partial signatures.
+/// `account_keys` lists each signing account's address with its keys, as returned by `parse_account_keys`.
+/// Errors naming the first signature which is invalid, or whose key is missing, revoked or uses an unsupported algorithm.
+pub fn verify_transaction_signatures(
+    tx: &Transaction,
+    account_keys: &[(&str, &[ParsedKey])],
+) -> Result<()> {
+    verify_transaction_signatures_with_domain_tag(tx, account_keys, TRANSACTION_DOMAIN_TAG)
+}
+/// Verifies like `verify_transaction_signatures`, for transactions signed with `sign_transaction_with_domain_tag`.
+pub fn verify_transaction_signatures_with_domain_tag(
+    tx: &Transaction,
+    account_keys: &[(&str, &[ParsedKey])],
+    domain_tag: [u8; 32],
+) -> Result<()> {
+    let payload = [&domain_tag[..], &payload_from_transaction(tx)?].concat();
+    let envelope = [
+        &domain_tag[..],
+        &envelope_from_transaction(tx, &tx.payload_signatures)?,
+    ]
+    .concat();
+    let mut accounts = vec![];
+    for (address, keys) in account_keys {
+        accounts.push((decode_address(address)?, *keys));
+    }
+    let signatures = tx
+        .payload_signatures
+        .iter()
+        .map(|signature| ("payload", &payload, signature))
+        .chain(
+            tx.envelope_signatures
+                .iter()
+                .map(|signature| ("envelope", &envelope, signature)),
+        );
+    for (kind, message, signature) in signatures {
+        let signer = format!(
+            "{} signature by 0x{} key {}",
+            kind,
+            hex::encode(&signature.address),
+            signature.key_id
+        );
+        let key = accounts
+            .iter()
+            .filter(|(address, _)| *address == signature.address)
+            .flat_map(|(_, keys)| keys.iter())
+            .find(|key| key.index == signature.key_id);
+        let key = match key {
+            Some(key) => key,
+            None => bail!("The {}: key not found", signer),
+        };
+        if key.revoked {
+            bail!("The {}: key is revoked", signer);
+        }
+        if key.sig_algo != SignatureAlgorithm::EcdsaP256 || key.hash_algo != HashAlgorithm::Sha3_256
+        {
+            bail!(
+                "The {}: unsupported key algorithms {:?}/{:?}",
+                signer,
+                key.sig_algo,
+                key.hash_algo
+            );
+        }
+        if !verify(message, &signature.signature, &key.public_key_hex)? {
+            bail!("The {} is invalid", signer);
+        }
+    }
+    Ok(())
+}
+/// Signs `message` with each signer, in order.
+/// ECDSA is CPU-bound, so when running inside a tokio runtime each signer is offloaded to the blocking pool and joined.
+#[cfg(feature = "transport")]
+async fn sign_all(message: Vec<u8>, signers: &[&Sign]) -> Result<Vec<Vec<u8>>> {
+    if signers.len() < 2 || tokio::runtime::Handle::try_current().is_err() {
+        return signers.iter().map(|signer| sign(&message, &signer.private_key)).collect();
+    }
+    let message = std::sync::Arc::new(message);
+    let handles: Vec<_> = signers
+        .iter()
+        .map(|signer| {
+            let message = message.clone();
+            let private_key = signer.private_key.clone();
+            tokio::task::spawn_blocking(move || sign(&message, &private_key))
+        })
+        .collect();
+    let mut signatures = Vec::with_capacity(handles.len());
+    for handle in handles {
+        signatures.push(handle.await??);
+    }
+    Ok(signatures)
+}
+/// Signs `message` with each signer, in order.
+#[cfg(not(feature = "transport"))]
+async fn sign_all(message: Vec<u8>, signers: &[&Sign]) -> Result<Vec<Vec<u8>>> {
+    signers.iter().map(|signer| sign(&message, &signer.private_key)).collect()
+}
+/// Signature algorithm of an account key, using Flow's numbering
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureAlgorithm {
+    EcdsaP256,
+    EcdsaSecp256k1,
+    Unknown(u32),
+}
+impl SignatureAlgorithm {
+    /// The raw value of the algorithm in Cadence's `SignatureAlgorithm` enum, which differs from Flow's numbering
+    pub fn cadence_raw_value(&self) -> Result<u8> {
+        match self {
+            SignatureAlgorithm::EcdsaP256 => Ok(1),
+            SignatureAlgorithm::EcdsaSecp256k1 => Ok(2),
+            SignatureAlgorithm::Unknown(code) => bail!("Unknown signature algorithm {}", code),
+        }
+    }
+}
+impl From<u32> for SignatureAlgorithm {
+    fn from(code: u32) -> Self {
+        match code {
+            2 => SignatureAlgorithm::EcdsaP256,
+            3 => SignatureAlgorithm::EcdsaSecp256k1,
+            _ => SignatureAlgorithm::Unknown(code),
+        }
+    }
+}
+/// Hash algorithm of an account key, using Flow's numbering
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum HashAlgorithm {
+    Sha2_256,
+    Sha2_384,
+    Sha3_256,
+    Sha3_384,
+    Unknown(u32),
+}
+impl HashAlgorithm {
+    /// The raw value of the algorithm in Cadence's `HashAlgorithm` enum
+    pub fn cadence_raw_value(&self) -> Result<u8> {
+        match self {
+            HashAlgorithm::Sha2_256 => Ok(1),
+            HashAlgorithm::Sha2_384 => Ok(2),
+            HashAlgorithm::Sha3_256 => Ok(3),
+            HashAlgorithm::Sha3_384 => Ok(4),
+            HashAlgorithm::Unknown(code) => bail!("Unknown hash algorithm {}", code),
+        }
+    }
+}
+impl From<u32> for HashAlgorithm {
+    fn from(code: u32) -> Self {
+        match code {
+            1 => HashAlgorithm::Sha2_256,
+            2 => HashAlgorithm::Sha2_384,
+            3 => HashAlgorithm::Sha3_256,
+            4 => HashAlgorithm::Sha3_384,
+            _ => HashAlgorithm::Unknown(code),
+        }
+    }
+}
+/// An account key, with its algorithms interpreted
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParsedKey {
+    pub index: u32,
+    pub public_key_hex: String,
+    pub sig_algo: SignatureAlgorithm,
+    pub hash_algo: HashAlgorithm,
+    pub weight: u32,
+    pub revoked: bool,
+    pub sequence_number: u64,
+}
+/// Interpret the keys of an account, eg. to pick a non-revoked key with enough weight before signing
+pub fn parse_account_keys(account: &flow::Account) -> Vec<ParsedKey> {
+    account
+        .keys
+        .iter()
+        .map(|key| ParsedKey {
+            index: key.id,
+            public_key_hex: hex::encode(&key.public_key),
+            sig_algo: SignatureAlgorithm::from(key.sign_algo),
+            hash_algo: HashAlgorithm::from(key.hash_algo),
+            weight: key.weight,
+            revoked: key.revoked,
+            sequence_number: key.sequence_number as u64,
+        })
+        .collect()
+}
+/// The current sequence number of the account key with the given id. Errors if the key is missing or revoked, as it cannot sign.
+pub fn key_sequence_number(account: &flow::Account, key_id: u32) -> Result<u64> {
+    match account.keys.iter().find(|key| key.id == key_id) {
+        Some(key) if key.revoked => bail!(
+            "key id {} on account {} is revoked",
+            key_id,
+            hex::encode(&account.address)
+        ),
+        Some(key) => Ok(key.sequence_number as u64),
+        None => bail!(
+            "key id {} does not exist on account {}",
+            key_id,
+            hex::encode(&account.address)
+        ),
+    }
+}
+/// The index of the account's first key which is not revoked and has at least `min_weight`, eg. 1000 to sign alone.
+/// Use it to pick a signing key rather than assuming key 0.
+pub fn first_usable_key(account: &flow::Account, min_weight: u32) -> Option<u32> {
+    account
+        .keys
+        .iter()
+        .find(|key| !key.revoked && key.weight >= min_weight)
+        .map(|key| key.id)
+}
+/// Hex-encodes an account key for the pre-1.0 `addPublicKey`.
+/// A raw 64 byte public key is wrapped with algo ECDSA_P256, hash SHA3_256 and weight 1000;
+/// anything else is assumed to be already encoded, eg. by `flow keys decode`, and is passed through verbatim.
+pub fn encode_account_key(key: &str) -> String {
+    let key = key.trim_start_matches("0x");
+    if key.len() == 128 {
+        format!("f847b840{}02038203e8", key)
+    } else {
+        key.to_owned()
+    }
+}
+/// A key for the `keys.add` API, built into the arguments of the `add_public_key` template.
+/// Defaults to ECDSA_P256, SHA3_256 and a full weight of 1000.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PublicKeyArgs {
+    pub public_key: Vec<u8>,
+    pub signature_algorithm: SignatureAlgorithm,
+    pub hash_algorithm: HashAlgorithm,
+    pub weight: f64,
+}
+impl PublicKeyArgs {
+    /// The raw 64 byte public key, without the SEC1 `0x04` prefix
+    pub fn new(public_key: Vec<u8>) -> Self {
+        PublicKeyArgs {
+            public_key,
+            signature_algorithm: SignatureAlgorithm::EcdsaP256,
+            hash_algorithm: HashAlgorithm::Sha3_256,
+            weight: 1000.0,
+        }
+    }
+    /// Sets the signature algorithm of the key
+    pub fn with_signature_algorithm(mut self, signature_algorithm: SignatureAlgorithm) -> Self {
+        self.signature_algorithm = signature_algorithm;
+        self
+    }
+    /// Sets the hash algorithm signatures by the key use
+    pub fn with_hash_algorithm(mut self, hash_algorithm: HashAlgorithm) -> Self {
+        self.hash_algorithm = hash_algorithm;
+        self
+    }
+    /// Sets the weight of the key, out of the 1000 needed to sign
+    pub fn with_weight(mut self, weight: f64) -> Self {
+        self.weight = weight;
+        self
+    }
+    /// The encoded `publicKey: [UInt8], signatureAlgorithm: UInt8, hashAlgorithm: UInt8, weight: UFix64` arguments
+    pub fn arguments(&self) -> Result<Vec<Vec<u8>>> {
+        Ok(vec![
+            Argument::bytes(&self.public_key).encode(),
+            Argument::uint8(self.signature_algorithm.cadence_raw_value()?).encode(),
+            Argument::uint8(self.hash_algorithm.cadence_raw_value()?).encode(),
+            Argument::ufix64(self.weight)?.encode(),
+        ])
+    }
+    /// The encoded `publicKeys: [[UInt8]], signatureAlgorithms: [UInt8], hashAlgorithms: [UInt8], weights: [UFix64]`
+    /// arguments of the `create_account` template, one element per key
+    pub fn array_arguments(keys: &[PublicKeyArgs]) -> Result<Vec<Vec<u8>>> {
+        let mut public_keys = Vec::with_capacity(keys.len());
+        let mut signature_algorithms = Vec::with_capacity(keys.len());
+        let mut hash_algorithms = Vec::with_capacity(keys.len());
+        let mut weights = Vec::with_capacity(keys.len());
+        for key in keys {
+            public_keys.push(Argument::bytes(&key.public_key).to_value());
+            signature_algorithms
+                .push(Argument::uint8(key.signature_algorithm.cadence_raw_value()?).to_value());
+            hash_algorithms
+                .push(Argument::uint8(key.hash_algorithm.cadence_raw_value()?).to_value());
+            weights.push(Argument::ufix64(key.weight)?.to_value());
+        }
+        Ok(vec![
+            Argument::array(public_keys).encode(),
+            Argument::array(signature_algorithms).encode(),
+            Argument::array(hash_algorithms).encode(),
+            Argument::array(weights).encode(),
+        ])
+    }
+}
+/// Process key arguments, for templates taking `publicKeys: [String]` of RLP-encoded account keys
+pub fn process_keys_args(account_keys: Vec<String>) -> Argument<Vec<Value>> {
+    Argument::array(
+        account_keys
+            .into_iter()
+            .map(|x| json!(Argument::string(encode_account_key(&x))))
+            .collect::<Vec<Value>>(),
+    )
+}
+/// Sign the provided transaction.
+/// You will first need to `build_transaction`.
+/// Signatures the transaction already has are kept, so a partially signed transaction can be passed on and signed again,
+/// eg. the payload on one machine and the envelope on another. Signers that have already signed are skipped.
+pub async fn sign_transaction(
+    built_transaction: Transaction,
+    payload_signatures: Vec<&Sign>,
+    envelope_signatures: Vec<&Sign>,
+) -> Result<Transaction> {
+    sign_transaction_with_domain_tag(
+        built_transaction,
+        payload_signatures,
+        envelope_signatures,
+        TRANSACTION_DOMAIN_TAG,
+    )
+    .await
+}
+/// Signs like `sign_transaction`, for chains using their own domain tag in place of `TRANSACTION_DOMAIN_TAG`. See `domain_tag`.
+pub async fn sign_transaction_with_domain_tag(
+    built_transaction: Transaction,
+    payload_signatures: Vec<&Sign>,
+    envelope_signatures: Vec<&Sign>,
+    domain_tag: [u8; 32],
+) -> Result<Transaction> {
+    let mut transaction = built_transaction;
+    let payload_signers = unsigned(&transaction.payload_signatures, payload_signatures)?;
+    let envelope_signers = unsigned(&transaction.envelope_signatures, envelope_signatures)?;
+    if !payload_signers.is_empty() && !transaction.envelope_signatures.is_empty() {
+        bail!("the payload cannot be signed once the envelope is, as the envelope signatures cover the payload signatures");
+    }
+    // the payload is identical for each of the payload signers, so encode it once
+    let fully_encoded: Vec<u8> =
+        [&domain_tag[..], &payload_from_transaction(&transaction)?].concat();
+    // the payload signers are independent of each other, so sign them concurrently
+    let signatures = sign_all(fully_encoded, &payload_signers).await?;
+    for (signer, signature) in payload_signers.iter().zip(signatures) {
+        transaction.payload_signatures.push(TransactionSignature {
+            address: decode_address(&signer.address)?,
+            key_id: signer.key_id,
+            signature,
+        });
+    }
+    sort_signatures(&mut transaction);
+    if envelope_signers.is_empty() {
+        return Ok(transaction);
+    }
+    // the envelope includes the payload signatures, which are now complete
+    let fully_encoded: Vec<u8> = [
+        &domain_tag[..],
+        &envelope_from_transaction(&transaction, &transaction.payload_signatures)?,
+    ]
+    .concat();
+    let signatures = sign_all(fully_encoded, &envelope_signers).await?;
+    for (signer, signature) in envelope_signers.iter().zip(signatures) {
+        transaction.envelope_signatures.push(TransactionSignature {
+            address: decode_address(&signer.address)?,
+            key_id: signer.key_id,
+            signature,
+        });
+    }
+    sort_signatures(&mut transaction);
+    Ok(transaction)
+}
+/// The signers without a signature in `existing`
+fn unsigned<'a>(
+    existing: &[TransactionSignature],
+    signers: Vec<&'a Sign>,
+) -> Result<Vec<&'a Sign>> {
+    let mut unsigned = vec![];
+    for signer in signers {
+        let address = decode_address(&signer.address)?;
+        if !existing
+            .iter()
+            .any(|x| x.address == address && x.key_id == signer.key_id)
+        {
+            unsigned.push(signer);
+        }
+    }
+    Ok(unsigned)
+}
+/// Combines signatures collected separately, eg. from other machines, into a partially signed transaction.
+/// Signatures by the payer are added to the envelope and all others to the payload; a signature for an address and key id the transaction already has is ignored.
+/// Both lists are kept in canonical order, by signer index and then key id.
+/// Errors when a payload signature would be added to a transaction whose envelope is already signed, as in `sign_transaction`.
+pub fn merge_signatures(
+    base: Transaction,
+    extra: Vec<TransactionSignature>,
+) -> Result<Transaction> {
+    let mut transaction = base;
+    let envelope_signed = !transaction.envelope_signatures.is_empty();
+    for signature in extra {
+        let payload = signature.address != transaction.payer;
+        let signatures = if payload {
+            &mut transaction.payload_signatures
+        } else {
+            &mut transaction.envelope_signatures
+        };
+        if signatures
+            .iter()
+            .any(|x| x.address == signature.address && x.key_id == signature.key_id)
+        {
+            continue;
+        }
+        if payload && envelope_signed {
+            bail!("the payload signature by 0x{} key {} cannot be added once the envelope is signed, as the envelope signatures cover the payload signatures", hex::encode(&signature.address), signature.key_id);
+        }
+        signatures.push(signature);
+    }
+    sort_signatures(&mut transaction);
+    Ok(transaction)
+}
+/// Orders both signature lists by signer index and then key id
+fn sort_signatures(transaction: &mut Transaction) {
+    let order = |transaction: &Transaction, signature: &TransactionSignature| {
+        (
+            signer_index(transaction, &signature.address).unwrap_or(usize::MAX),
+            signature.key_id,
+        )
+    };
+    let mut payload = std::mem::take(&mut transaction.payload_signatures);
+    payload.sort_by_key(|x| order(transaction, x));
+    transaction.payload_signatures = payload;
+    let mut envelope = std::mem::take(&mut transaction.envelope_signatures);
+    envelope.sort_by_key(|x| order(transaction, x));
+    transaction.envelope_signatures = envelope;
+}
+
+// ****************************************************
+// Cadence Values
+// ****************************************************
+
+use serde::de::DeserializeOwned;
+
+/// A value decoded from JSON-Cadence, such as the result of a script or the payload of an event.
+/// See https://docs.onflow.org/cadence/json-cadence-spec
+#[derive(Clone, Debug, PartialEq)]
+pub enum CadenceValue {
+    Void,
+    Optional(Option<Box<CadenceValue>>),
+    Bool(bool),
+    String(String),
+    Character(String),
+    /// Hex-encoded address, including the `0x` prefix
+    Address(String),
+    /// Any of the integer types, eg. `UInt64` or `Int256`. The value is kept as a decimal string.
+    Int { type_name: String, value: String },
+    /// The exact value scaled by 10^8, eg. `-1.5` is `Fix64(-150_000_000)`. See `fixed_point_string`.
+    Fix64(i64),
+    /// The exact value scaled by 10^8, eg. `1.5` is `UFix64(150_000_000)`. See `fixed_point_string`.
+    UFix64(u64),
+    Array(Vec<CadenceValue>),
+    Dictionary(Vec<(CadenceValue, CadenceValue)>),
+    Composite {
+        kind: CompositeKind,
+        id: String,
+        fields: Vec<(String, CadenceValue)>,
+    },
+    Path { domain: PathDomain, identifier: String },
+    /// The JSON-Cadence static type
+    Type(Value),
+    /// The JSON-Cadence capability object
+    Capability(Value),
+}
+
+impl CadenceValue {
+    /// Decode a JSON-Cadence payload, eg. `ExecuteScriptResponse.value` or `Event.payload`
+    pub fn decode(payload: &[u8]) -> Result<CadenceValue> {
+        let value: Value = from_slice(payload)?;
+        CadenceValue::from_json(&value)
+    }
+    /// Decode an already parsed JSON-Cadence value
+    pub fn from_json(json: &Value) -> Result<CadenceValue> {
+        let type_name = match json["type"].as_str() {
+            Some(type_name) => type_name,
+            None => bail!("JSON-Cadence value is missing its type: {}", json),
+        };
+        let value = &json["value"];
+        let decoded = match type_name {
+            "Void" => CadenceValue::Void,
+            "Optional" => {
+                if value.is_null() {
+                    CadenceValue::Optional(None)
+                } else {
+                    CadenceValue::Optional(Some(Box::new(CadenceValue::from_json(value)?)))
+                }
+            }
+            "Bool" => match value.as_bool() {
+                Some(b) => CadenceValue::Bool(b),
+                None => bail!("invalid Bool value: {}", value),
+            },
+            "String" => CadenceValue::String(json_str(value)?),
+            "Character" => CadenceValue::Character(json_str(value)?),
+            "Address" => CadenceValue::Address(json_str(value)?),
+            "Int" | "Int8" | "Int16" | "Int32" | "Int64" | "Int128" | "Int256" | "UInt" | "UInt8"
+            | "UInt16" | "UInt32" | "UInt64" | "UInt128" | "UInt256" | "Word8" | "Word16"
+            | "Word32" | "Word64" => CadenceValue::Int {
+                type_name: type_name.to_owned(),
+                value: json_str(value)?,
+            },
+            "Fix64" => CadenceValue::Fix64(parse_fixed_point(
+                &json_str(value)?,
+                i64::MIN as i128,
+                i64::MAX as i128,
+            )? as i64),
+            "UFix64" => {
+                CadenceValue::UFix64(
+                    parse_fixed_point(&json_str(value)?, 0, u64::MAX as i128)? as u64
+                )
+            }
+            "Array" => match value.as_array() {
+                Some(items) => CadenceValue::Array(
+                    items
+                        .iter()
+                        .map(CadenceValue::from_json)
+                        .collect::<Result<Vec<CadenceValue>>>()?,
+                ),
+                None => bail!("invalid Array value: {}", value),
+            },
+            "Dictionary" => match value.as_array() {
+                Some(entries) => CadenceValue::Dictionary(
+                    entries
+                        .iter()
+                        .map(|entry| {
+                            Ok((
+                                CadenceValue::from_json(&entry["key"])?,
+                                CadenceValue::from_json(&entry["value"])?,
+                            ))
+                        })
+                        .collect::<Result<Vec<(CadenceValue, CadenceValue)>>>()?,
+                ),
+                None => bail!("invalid Dictionary value: {}", value),
+            },
+            "Struct" | "Resource" | "Event" | "Contract" | "Enum" => {
+                let kind = match type_name {
+                    "Resource" => CompositeKind::Resource,
+                    "Event" => CompositeKind::Event,
+                    "Enum" => CompositeKind::Enum,
+                    _ => CompositeKind::Struct,
+                };
+                let fields = match value["fields"].as_array() {
+                    Some(fields) => fields
+                        .iter()
+                        .map(|field| {
+                            Ok((
+                                json_str(&field["name"])?,
+                                CadenceValue::from_json(&field["value"])?,
+                            ))
+                        })
+                        .collect::<Result<Vec<(String, CadenceValue)>>>()?,
+                    None => bail!("invalid {} value: {}", type_name, value),
+                };
+                CadenceValue::Composite {
+                    kind,
+                    id: json_str(&value["id"])?,
+                    fields,
+                }
+            }
+            "Path" => {
+                let domain = match value["domain"].as_str() {
+                    Some("storage") => PathDomain::Storage,
+                    Some("public") => PathDomain::Public,
+                    Some("private") => PathDomain::Private,
+                    _ => bail!("invalid Path domain: {}", value),
+                };
+                CadenceValue::Path {
+                    domain,
+                    identifier: json_str(&value["identifier"])?,
+                }
+            }
+            "Type" => CadenceValue::Type(value["staticType"].clone()),
+            "Capability" => CadenceValue::Capability(value.clone()),
+            _ => bail!("unsupported JSON-Cadence type: {}", type_name),
+        };
+        Ok(decoded)
+    }
+    /// Converts the value into plain JSON, without the JSON-Cadence type wrappers.
+    /// Optionals become `null` or their inner value, numbers become JSON numbers where they fit,
+    /// composites become objects keyed by field name and paths become `/domain/identifier` strings.
+    pub fn to_json(&self) -> Value {
+        match self {
+            CadenceValue::Void | CadenceValue::Optional(None) => Value::Null,
+            CadenceValue::Optional(Some(inner)) => inner.to_json(),
+            CadenceValue::Bool(b) => json!(b),
+            CadenceValue::String(s) | CadenceValue::Character(s) | CadenceValue::Address(s) => {
+                json!(s)
+            }
+            CadenceValue::Int { value, .. } => {
+                if let Ok(n) = value.parse::<u64>() {
+                    json!(n)
+                } else if let Ok(n) = value.parse::<i64>() {
+                    json!(n)
+                } else {
+                    json!(value)
+                }
+            }
+            // f64 is what most callers decode balances into; `fixed_point_string` is exact
+            CadenceValue::Fix64(_) | CadenceValue::UFix64(_) => {
+                json!(self
+                    .fixed_point_string()
+                    .unwrap_or_default()
+                    .parse::<f64>()
+                    .unwrap_or_default())
+            }
+            CadenceValue::Array(items) => {
+                Value::Array(items.iter().map(CadenceValue::to_json).collect())
+            }
+            CadenceValue::Dictionary(entries) => Value::Object(
+                entries
+                    .iter()
+                    .map(|(key, value)| {
+                        let key = match key.to_json() {
+                            Value::String(s) => s,
+                            other => other.to_string(),
+                        };
+                        (key, value.to_json())
+                    })
+                    .collect(),
+            ),
+            CadenceValue::Composite { fields, .. } => Value::Object(
+                fields
+                    .iter()
+                    .map(|(name, value)| (name.to_owned(), value.to_json()))
+                    .collect(),
+            ),
+            CadenceValue::Path { domain, identifier } => {
+                json!(format!("/{}/{}", domain.as_str(), identifier))
+            }
+            CadenceValue::Type(static_type) => static_type.clone(),
+            CadenceValue::Capability(capability) => capability.clone(),
+        }
+    }
+    /// Deserialize the value into a Rust type, via `to_json`.
+    /// eg. a `UFix64` decodes into `f64` and an `[Address]` into `Vec<String>`.
+    pub fn deserialize<T: DeserializeOwned>(&self) -> Result<T> {
+        Ok(serde_json::from_value(self.to_json())?)
+    }
+    /// A `Fix64` or `UFix64` as its exact decimal string with 8 decimal places, eg. `"184467440737.09551615"`.
+    /// `to_json` and `deserialize` give an f64, which loses precision on large values.
+    pub fn fixed_point_string(&self) -> Option<String> {
+        match self {
+            CadenceValue::Fix64(n) => Some(format_fixed_point(*n as i128)),
+            CadenceValue::UFix64(n) => Some(format_fixed_point(*n as i128)),
+            _ => None,
+        }
+    }
+}
+/// Reads a JSON string, or errors
+fn json_str(value: &Value) -> Result<String> {
+    match value.as_str() {
+        Some(s) => Ok(s.to_owned()),
+        None => bail!("expected a string, found: {}", value),
+    }
+}
+
+/// An event with its JSON-Cadence payload decoded
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodedEvent {
+    /// The fully qualified event type, eg. `A.0ae53cb6e3f42a79.FlowToken.TokensDeposited`
+    pub event_type: String,
+    pub transaction_index: u32,
+    pub event_index: u32,
+    pub value: CadenceValue,
+}
+impl DecodedEvent {
+    /// Decodes the payload of the event
+    pub fn decode(event: &flow::Event) -> Result<DecodedEvent> {
+        Ok(DecodedEvent {
+            event_type: event.r#type.clone(),
+            transaction_index: event.transaction_index,
+            event_index: event.event_index,
+            value: CadenceValue::decode(&event.payload)?,
+        })
+    }
+}
+/// Flatten the events of an `EventsResponse` into `(block_height, event_type, value)`, decoding each payload
+pub fn decode_events(resp: &EventsResponse) -> Result<Vec<(u64, String, CadenceValue)>> {
+    let mut events = vec![];
+    for block in &resp.results {
+        for event in &block.events {
+            events.push((
+                block.block_height,
+                event.r#type.clone(),
+                CadenceValue::decode(&event.payload)?,
+            ));
+        }
+    }
+    Ok(events)
+}
+
+// ****************************************************
+// Mock Access API
+// ****************************************************
+
+/// An in-process Access API serving canned responses, for testing code built on `FlowConnection` without a node.
+///
+/// ```ignore
+/// let mock = MockAccessApi::new().with_account(account);
+/// let mut connection = mock.connect().await?;
+/// connection.send_transaction(Some(signed)).await?;
+/// assert_eq!(mock.sent_transactions().len(), 1);
+/// ```
+#[cfg(feature = "mock")]
+pub mod mock {
+    use crate::flow::access_api_server::{AccessApi, AccessApiServer};
+    use crate::flow::*;
+    use crate::FlowConnection;
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tonic::{Request, Response, Status};
+
+    type RpcResult<T> = Result<Response<T>, Status>;
+
+    #[derive(Default)]
+    struct MockState {
+        accounts: HashMap<Vec<u8>, Account>,
+        latest_block: Block,
+        script_result: Vec<u8>,
+        transaction_results: HashMap<Vec<u8>, TransactionResultResponse>,
+        sent_transactions: Vec<Transaction>,
+        node_version_info: Option<NodeVersionInfo>,
+        stalled_sends: usize,
+        events: std::collections::BTreeMap<u64, Vec<Event>>,
+    }
+
+    /// Canned responses are shared between clones, so the mock can be inspected after it has been served.
+    /// Requests without a canned response fail with `NotFound`, or `Unimplemented` for the unsupported RPCs.
+    /// Like an Access node, results of unknown transactions are returned with the status `Unknown`.
+    #[derive(Clone, Default)]
+    pub struct MockAccessApi {
+        state: Arc<Mutex<MockState>>,
+    }
+
+    impl MockAccessApi {
+        /// A mock with no accounts, and a sealed latest block at height 1
+        pub fn new() -> Self {
+            MockAccessApi::default().with_latest_block(Block {
+                id: vec![1; 32],
+                height: 1,
+                ..Default::default()
+            })
+        }
+        /// Serve the account from `get_account`, keyed by its address
+        pub fn with_account(self, account: Account) -> Self {
+            self.state()
+                .accounts
+                .insert(account.address.clone(), account);
+            self
+        }
+        /// Serve the block as the latest block, and for any block id or height
+        pub fn with_latest_block(self, block: Block) -> Self {
+            self.state().latest_block = block;
+            self
+        }
+        /// Serve the node version info. Without it, `GetNodeVersionInfo` is unimplemented, like on nodes predating it.
+        pub fn with_node_version_info(self, info: NodeVersionInfo) -> Self {
+            self.state().node_version_info = Some(info);
+            self
+        }
+        /// Return the JSON-Cadence `value` from every script
+        pub fn with_script_result(self, value: Vec<u8>) -> Self {
+            self.state().script_result = value;
+            self
+        }
+        /// Serve the result for the transaction id.
+        /// Transactions sent without a canned result are sealed successfully, with no events.
+        pub fn with_transaction_result(
+            self,
+            id: Vec<u8>,
+            result: TransactionResultResponse,
+        ) -> Self {
+            self.state().transaction_results.insert(id, result);
+            self
+        }
+        /// Serve the events from `get_events_for_height_range`, as emitted in the block at the height.
+        /// Like an Access node, ranges wider than `MAX_EVENT_HEIGHT_RANGE` are rejected.
+        pub fn with_events(self, block_height: u64, events: Vec<Event>) -> Self {
+            self.state()
+                .events
+                .entry(block_height)
+                .or_default()
+                .extend(events);
+            self
+        }
+        /// Stall the next `count` calls to `send_transaction`, dropping their transactions,
+        /// so a client timeout leaves it unknown whether they were accepted
+        pub fn with_stalled_sends(self, count: usize) -> Self {
+            self.state().stalled_sends = count;
+            self
+        }
+        /// The transactions received by `send_transaction`, in order
+        pub fn sent_transactions(&self) -> Vec<Transaction> {
+            self.state().sent_transactions.clone()
+        }
+        /// Serve the mock on a local port, and connect to it
+        pub async fn connect(&self) -> anyhow::Result<FlowConnection<tonic::transport::Channel>> {
+            FlowConnection::connect(&self.serve().await?).await
+        }
+        /// Serve the mock on a local port, returning its address, eg. to build a channel with middleware
+        pub async fn serve(&self) -> anyhow::Result<String> {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await?;
+            let address = listener.local_addr()?;
+            let server = tonic::transport::Server::builder()
+                .add_service(AccessApiServer::new(self.clone()))
+                .serve_with_incoming(tokio_stream::wrappers::TcpListenerStream::new(listener));
+            tokio::spawn(server);
+            Ok(format!("http://{}", address))
+        }
+        fn state(&self) -> std::sync::MutexGuard<'_, MockState> {
+            self.state.lock().unwrap()
+        }
+        fn block(&self) -> Response<BlockResponse> {
+            Response::new(BlockResponse {
+                block: Some(self.state().latest_block.clone()),
+            })
+        }
+        fn block_header(&self) -> Response<BlockHeaderResponse> {
+            let block = self.state().latest_block.clone();
+            Response::new(BlockHeaderResponse {
+                block: Some(BlockHeader {
+                    id: block.id,
+                    parent_id: block.parent_id,
+                    height: block.height,
+                }),
+            })
+        }
+        fn account(&self, address: &[u8]) -> Option<Response<AccountResponse>> {
+            let account = self.state().accounts.get(address).cloned()?;
+            Some(Response::new(AccountResponse {
+                account: Some(account),
+            }))
+        }
+        fn script_result(&self) -> Response<ExecuteScriptResponse> {
+            Response::new(ExecuteScriptResponse {
+                value: self.state().script_result.clone(),
+            })
+        }
+    }
+
+    #[tonic::async_trait]
+    impl AccessApi for MockAccessApi {
+        async fn ping(&self, _: Request<PingRequest>) -> RpcResult<PingResponse> {
+            Ok(Response::new(PingResponse {}))
+        }
+        async fn get_latest_block_header(
+            &self,
+            _: Request<GetLatestBlockHeaderRequest>,
+        ) -> RpcResult<BlockHeaderResponse> {
+            Ok(self.block_header())
+        }
+        async fn get_block_header_by_id(
+            &self,
+            _: Request<GetBlockHeaderByIdRequest>,
+        ) -> RpcResult<BlockHeaderResponse> {
+            Ok(self.block_header())
+        }
+        async fn get_block_header_by_height(
+            &self,
+            _: Request<GetBlockHeaderByHeightRequest>,
+        ) -> RpcResult<BlockHeaderResponse> {
+            Ok(self.block_header())
+        }
+        async fn get_latest_block(
+            &self,
+            _: Request<GetLatestBlockRequest>,
+        ) -> RpcResult<BlockResponse> {
+            Ok(self.block())
+        }
+        async fn get_block_by_id(
+            &self,
+            _: Request<GetBlockByIdRequest>,
+        ) -> RpcResult<BlockResponse> {
+            Ok(self.block())
+        }
+        async fn get_block_by_height(
+            &self,
+            _: Request<GetBlockByHeightRequest>,
+        ) -> RpcResult<BlockResponse> {
+            Ok(self.block())
+        }
+        async fn get_collection_by_id(
+            &self,
+            _: Request<GetCollectionByIdRequest>,
+        ) -> RpcResult<CollectionResponse> {
+            Err(Status::not_found("collection not found"))
+        }
+        async fn send_transaction(
+            &self,
+            request: Request<SendTransactionRequest>,
+        ) -> RpcResult<SendTransactionResponse> {
+            let transaction = match request.into_inner().transaction {
+                Some(transaction) => transaction,
+                None => return Err(Status::invalid_argument("missing transaction")),
+            };
+            let stalled = {
+                let mut state = self.state();
+                let stalled = state.stalled_sends > 0;
+                state.stalled_sends = state.stalled_sends.saturating_sub(1);
+                stalled
+            };
+            if stalled {
+                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+                return Err(Status::unavailable("stalled"));
+            }
+            let id = crate::transaction_id(&transaction)
+                .map_err(|e| Status::invalid_argument(e.to_string()))?;
+            let mut state = self.state();
+            state
+                .transaction_results
+                .entry(id.clone())
+                .or_insert(TransactionResultResponse {
+                    status: TransactionStatus::Sealed as i32,
+                    ..Default::default()
+                });
+            state.sent_transactions.push(transaction);
+            Ok(Response::new(SendTransactionResponse { id }))
+        }
+        async fn get_transaction(
+            &self,
+            request: Request<GetTransactionRequest>,
+        ) -> RpcResult<TransactionResponse> {
+            let id = request.into_inner().id;
+            let state = self.state();
+            match state
+                .sent_transactions
+                .iter()
+                .find(|transaction| crate::transaction_id(transaction).ok().as_ref() == Some(&id))
+            {
+                Some(transaction) => Ok(Response::new(TransactionResponse {
+                    transaction: Some(transaction.clone()),
+                })),
+                None => Err(Status::not_found("transaction not found")),
+            }
+        }
+        async fn get_transaction_result(
+            &self,
+            request: Request<GetTransactionRequest>,
+        ) -> RpcResult<TransactionResultResponse> {
+            match self
+                .state()
+                .transaction_results
+                .get(&request.into_inner().id)
+            {
+                Some(result) => Ok(Response::new(result.clone())),
+                None => Ok(Response::new(TransactionResultResponse {
+                    status: TransactionStatus::Unknown as i32,
+                    ..Default::default()
+                })),
+            }
+        }
+        async fn get_account_at_latest_block(
+            &self,
+            request: Request<GetAccountAtLatestBlockRequest>,
+        ) -> RpcResult<AccountResponse> {
+            self.account(&request.into_inner().address)
+                .ok_or_else(|| Status::not_found("account not found"))
+        }
+        async fn get_account_at_block_height(
+            &self,
+            request: Request<GetAccountAtBlockHeightRequest>,
+        ) -> RpcResult<AccountResponse> {
+            self.account(&request.into_inner().address)
+                .ok_or_else(|| Status::not_found("account not found"))
+        }
+        async fn execute_script_at_latest_block(
+            &self,
+            _: Request<ExecuteScriptAtLatestBlockRequest>,
+        ) -> RpcResult<ExecuteScriptResponse> {
+            Ok(self.script_result())
+        }
+        async fn execute_script_at_block_id(
+            &self,
+            _: Request<ExecuteScriptAtBlockIdRequest>,
+        ) -> RpcResult<ExecuteScriptResponse> {
+            Ok(self.script_result())
+        }
+        async fn execute_script_at_block_height(
+            &self,
+            _: Request<ExecuteScriptAtBlockHeightRequest>,
+        ) -> RpcResult<ExecuteScriptResponse> {
+            Ok(self.script_result())
+        }
+        async fn get_events_for_height_range(
+            &self,
+            request: Request<GetEventsForHeightRangeRequest>,
+        ) -> RpcResult<EventsResponse> {
+            let request = request.into_inner();
+            if request.end_height < request.start_height
+                || request.end_height - request.start_height >= crate::MAX_EVENT_HEIGHT_RANGE
+            {
+                return Err(Status::invalid_argument("invalid height range"));
+            }
+            let results = self
+                .state()
+                .events
+                .range(request.start_height..=request.end_height)
+                .map(|(height, events)| events_response::Result {
+                    block_height: *height,
+                    events: events
+                        .iter()
+                        .filter(|event| event.r#type == request.r#type)
+                        .cloned()
+                        .collect(),
+                    ..Default::default()
+                })
+                .collect();
+            Ok(Response::new(EventsResponse { results }))
+        }
+        async fn get_events_for_block_i_ds(
+            &self,
+            _: Request<GetEventsForBlockIdsRequest>,
+        ) -> RpcResult<EventsResponse> {
+            Ok(Response::new(EventsResponse::default()))
+        }
+        async fn get_network_parameters(
+            &self,
+            _: Request<GetNetworkParametersRequest>,
+        ) -> RpcResult<GetNetworkParametersResponse> {
+            Err(Status::unimplemented("not supported by the mock"))
+        }
+        async fn get_latest_protocol_state_snapshot(
+            &self,
+            _: Request<GetLatestProtocolStateSnapshotRequest>,
+        ) -> RpcResult<ProtocolStateSnapshotResponse> {
+            Err(Status::unimplemented("not supported by the mock"))
+        }
+        async fn get_execution_result_for_block_id(
+            &self,
+            _: Request<GetExecutionResultForBlockIdRequest>,
+        ) -> RpcResult<ExecutionResultForBlockIdResponse> {
+            Err(Status::unimplemented("not supported by the mock"))
+        }
+        async fn get_node_version_info(
+            &self,
+            _: Request<GetNodeVersionInfoRequest>,
+        ) -> RpcResult<NodeVersionInfoResponse> {
+            match self.state().node_version_info.clone() {
+                Some(info) => Ok(Response::new(NodeVersionInfoResponse { info: Some(info) })),
+                None => Err(Status::unimplemented("unknown method GetNodeVersionInfo")),
+            }
+        }
+    }
+}
+
+// ****************************************************
+// Testing
+// ****************************************************
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn padding_rejects_over_length_input() {
+        let mut address = b"0x0000000000000001".to_vec();
+        assert!(padding(&mut address, 8).is_err());
+
+        let mut address = vec![1, 2, 3];
+        padding(&mut address, 8).unwrap();
+        assert_eq!(address, vec![1, 2, 3, 0, 0, 0, 0, 0]);
+    }
+
+    #[tokio::test]
+    async fn build_transaction_rejects_malformed_ids() {
+        let block_id = "f0e4c2f76c58916ec258f246851bea091d14d4247a2fc3e18694461b1816e13b";
+        let proposer = |address: Vec<u8>| TransactionProposalKey {
+            address,
+            key_id: 0,
+            sequence_number: 0,
+        };
+        let build = |reference_block_id: Vec<u8>, address: Vec<u8>| {
+            build_transaction(
+                vec![],
+                vec![],
+                reference_block_id,
+                DEFAULT_GAS_LIMIT,
+                proposer(address),
+                vec![],
+                "0000000000000001".to_owned(),
+            )
+        };
+        // the hex id itself, rather than its decoded bytes
+        assert!(build(block_id.as_bytes().to_vec(), vec![0; 8])
+            .await
+            .is_err());
+        assert!(
+            build(hex::decode(block_id).unwrap(), b"0000000000000001".to_vec())
+                .await
+                .is_err()
+        );
+        assert!(build(hex::decode(block_id).unwrap(), vec![0; 8])
+            .await
+            .is_ok());
+    }
+
+    #[tokio::test]
+    async fn verify_partial_signatures() {
+        let key = |address: &str, index: u32| {
+            let signing_key = SigningKey::random(&mut OsRng);
+            let public_key = signing_key.verifying_key().to_encoded_point(false);
+            let parsed = ParsedKey {
+                index,
+                public_key_hex: hex::encode(&public_key.as_bytes()[1..]),
+                sig_algo: SignatureAlgorithm::EcdsaP256,
+                hash_algo: HashAlgorithm::Sha3_256,
+                weight: 1000,
+                revoked: false,
+                sequence_number: 0,
+            };
+            (
+                Sign::new(address, index, &hex::encode(signing_key.to_bytes())),
+                parsed,
+            )
+        };
+        let (authorizer, authorizer_key) = key("0000000000000001", 4);
+        let (payer, payer_key) = key("0000000000000002", 0);
+        let mut transaction = vector_transaction();
+        transaction.payer = hex::decode("0000000000000002").unwrap();
+        let signed = sign_transaction(transaction, vec![&authorizer], vec![&payer])
+            .await
+            .unwrap();
+        let authorizer_keys = [authorizer_key];
+        let payer_keys = [payer_key];
+        let accounts = [
+            ("0x0000000000000001", &authorizer_keys[..]),
+            ("0x0000000000000002", &payer_keys[..]),
+        ];
+        verify_transaction_signatures(&signed, &accounts).unwrap();
+
+        let mut tampered = signed.clone();
+        tampered.payload_signatures[0].signature[10] ^= 1;
+        assert!(verify_transaction_signatures(&tampered, &accounts).is_err());
+        assert!(verify_transaction_signatures(&signed, &accounts[..1]).is_err());
+
+        // signatures only verify under the domain tag they were made with
+        let tag = domain_tag("MYCHAIN-V0.0-transaction").unwrap();
+        let mut transaction = vector_transaction();
+        transaction.payer = hex::decode("0000000000000002").unwrap();
+        let signed =
+            sign_transaction_with_domain_tag(transaction, vec![&authorizer], vec![&payer], tag)
+                .await
+                .unwrap();
+        verify_transaction_signatures_with_domain_tag(&signed, &accounts, tag).unwrap();
+        assert!(verify_transaction_signatures(&signed, &accounts).is_err());
+    }
+
+    #[test]
+    fn typed_array_arguments() {
+        assert_eq!(
+            Argument::address_array(&["0x01", "f8d6e0586b0a20c7"]).to_value(),
+            json!({
+                "type": "Array",
+                "value": [
+                    {"type": "Address", "value": "0x01"},
+                    {"type": "Address", "value": "0xf8d6e0586b0a20c7"}
+                ]
+            })
+        );
+        assert_eq!(
+            Argument::uint64_array(&[1, 2]).to_value(),
+            json!({"type": "Array", "value": [{"type": "UInt64", "value": "1"}, {"type": "UInt64", "value": "2"}]})
+        );
+        assert_eq!(
+            Argument::string_array(&["a"]).to_value(),
+            json!({"type": "Array", "value": [{"type": "String", "value": "a"}]})
+        );
+        assert_eq!(
+            Argument::ufix64_array(&[1.5]).unwrap().to_value(),
+            json!({"type": "Array", "value": [{"type": "UFix64", "value": "1.50000000"}]})
+        );
+        assert!(Argument::ufix64_array(&[1.0, -1.0]).is_err());
+
+        let (addresses, amounts) =
+            Argument::address_amount_pairs(&[("0x01", 1.5), ("f8d6e0586b0a20c7", 2.0)]).unwrap();
+        assert_eq!(
+            addresses.to_value(),
+            Argument::address_array(&["0000000000000001", "f8d6e0586b0a20c7"]).to_value()
+        );
+        assert_eq!(
+            amounts.to_value(),
+            Argument::ufix64_array(&[1.5, 2.0]).unwrap().to_value()
+        );
+        assert!(Argument::address_amount_pairs(&[("0xnot-hex", 1.0)]).is_err());
+        assert!(Argument::address_amount_pairs(&[("0x01", -1.0)]).is_err());
+    }
+
+    #[test]
+    fn parse_argument_files() {
+        let arguments = parse_arguments(
+            r#"[
+                {"type": "UFix64", "value": "10.50000000"},
+                {"type": "Address", "value": "0x01cf0e2f2f715450"},
+                {"type": "Optional", "value": null}
+            ]"#,
+        )
+        .unwrap();
+        assert_eq!(
+            arguments,
+            vec![
+                Argument::ufix64(10.5).unwrap().encode(),
+                Argument::address("0x01cf0e2f2f715450".to_owned()).encode(),
+                Argument::optional(None).encode(),
+            ]
+        );
+        assert!(parse_arguments(r#"{"type": "Bool", "value": true}"#).is_err());
+        assert!(parse_arguments(r#"[{"type": "Bool", "value": "true"}]"#).is_err());
+        assert!(parse_arguments(r#"[{"value": "1"}]"#).is_err());
+    }
+
+    #[test]
+    fn domain_tags_are_padded() {
+        assert_eq!(
+            domain_tag("FLOW-V0.0-transaction").unwrap(),
+            TRANSACTION_DOMAIN_TAG
+        );
+        assert_eq!(domain_tag("FLOW-V0.0-user").unwrap(), USER_DOMAIN_TAG);
+        assert!(domain_tag(&"x".repeat(33)).is_err());
+    }
+
+    #[test]
+    fn address_checksums() {
+        for network in [Network::Mainnet, Network::Testnet, Network::Emulator].iter() {
+            assert!(is_valid_address(network.fungible_token_address(), *network));
+            assert!(is_valid_address(network.flow_token_address(), *network));
+        }
+        assert!(is_valid_address(
+            EMULATOR_SERVICE_ACCOUNT,
+            Network::Emulator
+        ));
+        assert!(!is_valid_address(
+            EMULATOR_SERVICE_ACCOUNT,
+            Network::Mainnet
+        ));
+        // one flipped bit
+        assert!(!is_valid_address("0x1654653399040a60", Network::Mainnet));
+        assert!(!is_valid_address("0x0000000000000000", Network::Mainnet));
+        assert!(!is_valid_address("not an address", Network::Mainnet));
+    }
+
+    #[cfg(feature = "transport")]
+    #[test]
+    fn created_account_address_is_read_by_field_name() {
+        let event = |payload: &str| Event {
+            r#type: "flow.AccountCreated".to_owned(),
+            payload: payload.as_bytes().to_vec(),
+            ..Default::default()
+        };
+        let created = event(
+            r#"{"type":"Event","value":{"id":"flow.AccountCreated","fields":[{"name":"address","value":{"type":"Address","value":"0x1cb159857af02018"}}]}}"#,
+        );
+        assert_eq!(
+            created_account_address(&[created]).unwrap(),
+            "1cb159857af02018"
+        );
+        // short addresses are padded to 8 bytes
+        let created = event(
+            r#"{"type":"Event","value":{"id":"flow.AccountCreated","fields":[{"name":"other","value":{"type":"Bool","value":true}},{"name":"address","value":{"type":"Address","value":"0x01"}}]}}"#,
+        );
+        assert_eq!(
+            created_account_address(&[created]).unwrap(),
+            "0000000000000001"
+        );
+        for payload in [
+            r#"{"type":"Event","value":{"id":"flow.AccountCreated","fields":[]}}"#,
+            r#"{"type":"Event","value":{"id":"flow.AccountCreated","fields":[{"name":"address","value":{"type":"Optional","value":null}}]}}"#,
+            "not json",
+        ] {
+            assert!(created_account_address(&[event(payload)]).is_err());
+        }
+        assert!(created_account_address(&[]).is_err());
+    }
+
+    #[test]
+    fn guarantee_signer_positions() {
+        let guarantee = flow::CollectionGuarantee {
+            collection_id: vec![0xab; 32],
+            signer_indices: vec![0x12, 0x34, 0x56, 0x78, 0b1010_0000, 0b0000_0001],
+            ..Default::default()
+        };
+        assert_eq!(guarantee.collection_id(), "ab".repeat(32));
+        assert_eq!(guarantee.signer_indices(), "12345678a001");
+        assert_eq!(guarantee.signer_positions(), vec![0, 2, 15]);
+        assert!(flow::CollectionGuarantee::default()
+            .signer_positions()
+            .is_empty());
+
+        use prost::Message;
+        // signer_ids is field 5, signer_indices field 6, both length-delimited
+        let wire = [
+            0x2a,
+            0x02,
+            0xaa,
+            0xbb,
+            0x32,
+            0x05,
+            0x12,
+            0x34,
+            0x56,
+            0x78,
+            0b1010_0000,
+        ];
+        let guarantee = flow::CollectionGuarantee::decode(&wire[..]).unwrap();
+        assert_eq!(guarantee.signer_ids(), vec!["aabb"]);
+        assert_eq!(guarantee.signer_indices(), "12345678a0");
+        assert_eq!(guarantee.signer_positions(), vec![0, 2]);
+    }
+
+    #[test]
+    fn transaction_result_views() {
+        use prost::Message;
+        // computation_usage is field 10, a varint
+        let result = flow::TransactionResultResponse::decode(&[0x50, 0xd2, 0x09][..]).unwrap();
+        assert_eq!(result.computation_used(), 1234);
+
+        let payload = json!({
+            "type": "Event",
+            "value": {
+                "id": "A.0ae53cb6e3f42a79.FlowToken.TokensDeposited",
+                "fields": [
+                    {"name": "amount", "value": {"type": "UFix64", "value": "1.50000000"}},
+                    {"name": "to", "value": {"type": "Optional", "value": {"type": "Address", "value": "0x01cf0e2f2f715450"}}}
+                ]
+            }
+        });
+        let result = flow::TransactionResultResponse {
+            events: vec![flow::Event {
+                r#type: "A.0ae53cb6e3f42a79.FlowToken.TokensDeposited".to_owned(),
+                event_index: 2,
+                payload: to_vec(&payload).unwrap(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let events = result.decoded_events().unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].event_type,
+            "A.0ae53cb6e3f42a79.FlowToken.TokensDeposited"
+        );
+        assert_eq!(events[0].event_index, 2);
+        assert_eq!(
+            events[0].value,
+            CadenceValue::Composite {
+                kind: CompositeKind::Event,
+                id: "A.0ae53cb6e3f42a79.FlowToken.TokensDeposited".to_owned(),
+                fields: vec![
+                    ("amount".to_owned(), CadenceValue::UFix64(150_000_000)),
+                    (
+                        "to".to_owned(),
+                        CadenceValue::Optional(Some(Box::new(CadenceValue::Address(
+                            "0x01cf0e2f2f715450".to_owned()
+                        ))))
+                    ),
+                ],
+            }
+        );
+
+        let malformed = flow::TransactionResultResponse {
+            events: vec![flow::Event {
+                payload: b"not json".to_vec(),
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        assert!(malformed.decoded_events().is_err());
+    }
+
+    #[cfg(feature = "transport")]
+    #[test]
+    fn only_node_rejections_are_classified() {
+        let rejected =
+            |status| with_rejection(request_error(&SendTransactionRequest::default(), status));
+        let error = rejected(tonic::Status::invalid_argument("reference block expired"));
+        assert!(matches!(
+            error.downcast_ref::<FlowError>(),
+            Some(FlowError::ReferenceBlockExpired(_))
+        ));
+        assert_eq!(
+            error.downcast_ref::<tonic::Status>().unwrap().code(),
+            tonic::Code::InvalidArgument
+        );
+        assert!(matches!(
+            rejected(tonic::Status::invalid_argument("invalid signature"))
+                .downcast_ref::<FlowError>(),
+            Some(FlowError::InvalidSignature(_))
+        ));
+        // transport failures and other codes are left alone, whatever their message
+        for status in [
+            tonic::Status::unknown("transport error: connection already closed"),
+            tonic::Status::unavailable("signature verifier unavailable"),
+        ] {
+            let error = rejected(status);
+            assert!(error.downcast_ref::<FlowError>().is_none());
+            assert!(error.downcast_ref::<tonic::Status>().is_some());
+        }
+        // a server cancelling an expired deadline is a timeout, not a rejection
+        assert!(matches!(
+            rejected(tonic::Status::cancelled("Timeout expired")).downcast_ref::<FlowError>(),
+            Some(FlowError::Timeout(_))
+        ));
+    }
+
+    #[test]
+    fn timestamp_round_trip() {
+        let epoch = std::time::UNIX_EPOCH;
+        for time in &[
+            epoch + std::time::Duration::new(1_650_000_000, 123_456_789),
+            epoch - std::time::Duration::new(10, 250_000_000),
+            epoch,
+        ] {
+            let timestamp = flow::Timestamp::from(*time);
+            assert!((0..1_000_000_000).contains(&timestamp.nanos));
+            assert_eq!(timestamp.to_system_time(), *time);
+        }
+        let timestamp = flow::Timestamp::from(epoch - std::time::Duration::new(10, 250_000_000));
+        assert_eq!((timestamp.seconds, timestamp.nanos), (-11, 750_000_000));
+        #[cfg(feature = "chrono")]
+        {
+            let time = flow::Timestamp::from(epoch + std::time::Duration::new(1_650_000_000, 5))
+                .to_date_time()
+                .unwrap();
+            assert_eq!(
+                (time.timestamp(), time.timestamp_subsec_nanos()),
+                (1_650_000_000, 5)
+            );
+        }
+    }
+
+    #[test]
+    fn bytes_argument() {
+        let argument = Argument::bytes(&[0, 1, 255]);
+        assert_eq!(
+            json!(argument),
+            json!({
+                "type": "Array",
+                "value": [
+                    {"type": "UInt8", "value": "0"},
+                    {"type": "UInt8", "value": "1"},
+                    {"type": "UInt8", "value": "255"}
+                ]
+            })
+        );
+        let decoded = CadenceValue::decode(&argument.encode()).unwrap();
+        let bytes: Vec<u8> = decoded.deserialize().unwrap();
+        assert_eq!(bytes, vec![0, 1, 255]);
+    }
+
+    #[test]
+    fn argument_encoding_matches_spec() {
+        let cases: Vec<(Vec<u8>, &str)> = vec![
+            (
+                Argument::boolean(true).encode(),
+                r#"{"type":"Bool","value":true}"#,
+            ),
+            (
+                Argument::str("foo").encode(),
+                r#"{"type":"String","value":"foo"}"#,
+            ),
+            (
+                Argument::string("foo".to_owned()).encode(),
+                r#"{"type":"String","value":"foo"}"#,
+            ),
+            (
+                Argument::character("a").unwrap().encode(),
+                r#"{"type":"Character","value":"a"}"#,
+            ),
+            (
+                Argument::ufix64(12.3).unwrap().encode(),
+                r#"{"type":"UFix64","value":"12.30000000"}"#,
+            ),
+            (
+                Argument::fix64(-12.3).unwrap().encode(),
+                r#"{"type":"Fix64","value":"-12.30000000"}"#,
+            ),
+            (
+                Argument::uint64(42).encode(),
+                r#"{"type":"UInt64","value":"42"}"#,
+            ),
+            (
+                Argument::int64(-42).encode(),
+                r#"{"type":"Int64","value":"-42"}"#,
+            ),
+            (
+                Argument::uint256("42").unwrap().encode(),
+                r#"{"type":"UInt256","value":"42"}"#,
+            ),
+            (
+                Argument::int256("-42").unwrap().encode(),
+                r#"{"type":"Int256","value":"-42"}"#,
+            ),
+            (
+                Argument::address("f8d6e0586b0a20c7".to_owned()).encode(),
+                r#"{"type":"Address","value":"0xf8d6e0586b0a20c7"}"#,
+            ),
+            (
+                Argument::array(vec![json!(Argument::uint64(1))]).encode(),
+                r#"{"type":"Array","value":[{"type":"UInt64","value":"1"}]}"#,
+            ),
+            (
+                Argument::dictionary_str(vec![("k".to_owned(), "v".to_owned())]).encode(),
+                r#"{"type":"Dictionary","value":[{"key":{"type":"String","value":"k"},"value":{"type":"String","value":"v"}}]}"#,
+            ),
+            (
+                Argument::path(PathDomain::Storage, "flowTokenVault").encode(),
+                r#"{"type":"Path","value":{"domain":"storage","identifier":"flowTokenVault"}}"#,
+            ),
+            (Argument::void().encode(), r#"{"type":"Void","value":null}"#),
+            (
+                Argument::type_value("Int").unwrap().encode(),
+                r#"{"type":"Type","value":{"staticType":{"kind":"Int"}}}"#,
+            ),
+            (
+                Argument::struct_value(
+                    "A.0000000000000001.Foo.Bar",
+                    vec![("x".to_owned(), json!(Argument::uint64(1)))],
+                )
+                .encode(),
+                r#"{"type":"Struct","value":{"fields":[{"name":"x","value":{"type":"UInt64","value":"1"}}],"id":"A.0000000000000001.Foo.Bar"}}"#,
+            ),
+        ];
+        for (encoded, expected) in cases {
+            assert_eq!(String::from_utf8(encoded).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn static_type_encoding() {
+        let static_type = |static_type, kind| {
+            Argument::type_value_with_kind(static_type, kind)
+                .unwrap()
+                .to_value()["value"]["staticType"]
+                .clone()
         };
-        let contract_name_arg = Argument::str(contract_name);
-        let transaction: Transaction = build_transaction(
-            update_contract_template.to_vec(),
-            vec![contract_name_arg.encode_str()],
-            latest_block.block.unwrap().id,
-            1000,
-            proposer,
-            vec![payer.to_owned()],
-            payer.to_owned(),
+        let composite = |kind: &str, id: &str| json!({"kind": kind, "typeID": id, "fields": [], "initializers": [], "type": ""});
+        let bar = "A.0000000000000001.Foo.Bar";
+        assert_eq!(
+            static_type(bar, CompositeKind::Struct),
+            composite("Struct", bar)
+        );
+        assert_eq!(
+            static_type("[Int; 3]?", CompositeKind::Struct),
+            json!({"kind": "Optional", "type": {"kind": "ConstantSizedArray", "type": {"kind": "Int"}, "size": 3}})
+        );
+        assert_eq!(
+            static_type(
+                "{String: [A.0000000000000001.Foo.Bar]}",
+                CompositeKind::Struct
+            ),
+            json!({"kind": "Dictionary", "key": {"kind": "String"}, "value": {"kind": "VariableSizedArray", "type": composite("Struct", bar)}})
+        );
+        assert_eq!(
+            static_type("{A.0000000000000001.Foo.Receiver}", CompositeKind::Resource),
+            json!({"kind": "Intersection", "typeID": "", "types": [composite("ResourceInterface", "A.0000000000000001.Foo.Receiver")]})
+        );
+        assert_eq!(
+            static_type(
+                "A.0000000000000001.Foo.Vault{A.0000000000000001.Foo.Receiver}",
+                CompositeKind::Resource
+            )["restrictions"][0]["kind"],
+            "ResourceInterface"
+        );
+
+        // capabilities borrow a reference type
+        let vault = "A.0ae53cb6e3f42a79.FlowToken.Vault";
+        let capability = Argument::capability(
+            Argument::path(PathDomain::Public, "flowTokenBalance"),
+            "f8d6e0586b0a20c7",
+            &format!("&{}", vault),
         )
-        .await?;
-        let signature = Sign {
-            address: payer.to_owned(),
-            key_id,
-            private_key: payer_private_key.to_owned(),
-        };
-        let transaction: Option<Transaction> =
-            sign_transaction(transaction, vec![], vec![&signature]).await?;
-        let transaction: SendTransactionResponse = self.send_transaction(transaction).await?;
+        .unwrap();
+        assert_eq!(
+            capability.to_value()["value"]["address"],
+            "0xf8d6e0586b0a20c7"
+        );
+        assert_eq!(
+            capability.to_value()["value"]["borrowType"],
+            json!({
+                "kind": "Reference",
+                "authorized": false,
+                "authorization": {"kind": "Unauthorized", "entitlements": null},
+                "type": composite("Resource", vault),
+            })
+        );
+        assert_eq!(
+            static_type(
+                "auth(A.1.FungibleToken.Withdraw) &A.0ae53cb6e3f42a79.FlowToken.Vault",
+                CompositeKind::Resource
+            )["authorization"],
+            json!({"kind": "EntitlementConjunctionSet", "entitlements": [{"kind": "Entitlement", "typeID": "A.1.FungibleToken.Withdraw"}]})
+        );
 
-        Ok(transaction)
+        // qualified types default to resources, as NFTs are
+        assert_eq!(
+            Argument::type_value(bar).unwrap().to_value()["value"]["staticType"],
+            composite("Resource", bar)
+        );
+        assert!(Argument::type_value("[Int").is_err());
+        assert!(Argument::type_value("Int}").is_err());
+        assert!(Argument::type_value("auth(Withdraw) Vault").is_err());
+        assert!(Argument::capability(
+            Argument::path(PathDomain::Public, "flowTokenBalance"),
+            "0xnot-hex",
+            "&Int"
+        )
+        .is_err());
     }
-}
 
-// ****************************************************
-// Utility Functionality
-// ****************************************************
+    #[test]
+    fn nested_optional_struct_array() {
+        let point = |x: u64| {
+            Argument::struct_value(
+                "A.0000000000000001.Foo.Point",
+                vec![("x".to_owned(), Argument::uint64(x).to_value())],
+            )
+            .to_value()
+        };
+        let argument = Argument::array(vec![
+            Argument::optional(Some(point(1))).to_value(),
+            Argument::optional(None).to_value(),
+        ]);
+        assert_eq!(
+            argument.to_value(),
+            json!({
+                "type": "Array",
+                "value": [
+                    {
+                        "type": "Optional",
+                        "value": {
+                            "type": "Struct",
+                            "value": {
+                                "id": "A.0000000000000001.Foo.Point",
+                                "fields": [{"name": "x", "value": {"type": "UInt64", "value": "1"}}]
+                            }
+                        }
+                    },
+                    {"type": "Optional", "value": null}
+                ]
+            })
+        );
+        let decoded = CadenceValue::decode(&argument.encode()).unwrap();
+        assert_eq!(decoded.to_json(), json!([{"x": 1}, null]));
+    }
 
-use serde::Serialize;
-pub use serde_json::{from_slice, json, to_vec, Value};
-use tokio::time::{sleep, Duration};
+    #[test]
+    fn public_key_arguments() {
+        let arguments = PublicKeyArgs::new(vec![1, 2])
+            .with_signature_algorithm(SignatureAlgorithm::EcdsaSecp256k1)
+            .with_weight(500.0)
+            .arguments()
+            .unwrap();
+        let arguments: Vec<String> = arguments
+            .into_iter()
+            .map(|argument| String::from_utf8(argument).unwrap())
+            .collect();
+        assert_eq!(
+            arguments,
+            vec![
+                r#"{"type":"Array","value":[{"type":"UInt8","value":"1"},{"type":"UInt8","value":"2"}]}"#,
+                r#"{"type":"UInt8","value":"2"}"#,
+                r#"{"type":"UInt8","value":"3"}"#,
+                r#"{"type":"UFix64","value":"500.00000000"}"#,
+            ]
+        );
+        assert!(PublicKeyArgs::new(vec![])
+            .with_hash_algorithm(HashAlgorithm::Unknown(9))
+            .arguments()
+            .is_err());
+    }
 
-/// This is our argument builder.
-#[derive(Serialize)]
-pub struct Argument<T> {
-    r#type: &'static str,
-    value: T,
-}
-/// Argument builder assuming a vec<String>
-impl Argument<Vec<Value>> {
-    /// Argument from array
-    pub fn array(values: Vec<Value>) -> Argument<Vec<Value>> {
-        Argument {
-            r#type: "Array",
-            value: values,
-        }
+    #[test]
+    fn weighted_account_key_arguments() {
+        let keys = vec![
+            PublicKeyArgs::new(vec![1]).with_weight(500.0),
+            PublicKeyArgs::new(vec![2])
+                .with_signature_algorithm(SignatureAlgorithm::EcdsaSecp256k1)
+                .with_hash_algorithm(HashAlgorithm::Sha2_256),
+        ];
+        let arguments: Vec<String> = PublicKeyArgs::array_arguments(&keys)
+            .unwrap()
+            .into_iter()
+            .map(|argument| String::from_utf8(argument).unwrap())
+            .collect();
+        assert_eq!(
+            arguments,
+            vec![
+                r#"{"type":"Array","value":[{"type":"Array","value":[{"type":"UInt8","value":"1"}]},{"type":"Array","value":[{"type":"UInt8","value":"2"}]}]}"#,
+                r#"{"type":"Array","value":[{"type":"UInt8","value":"1"},{"type":"UInt8","value":"2"}]}"#,
+                r#"{"type":"Array","value":[{"type":"UInt8","value":"3"},{"type":"UInt8","value":"1"}]}"#,
+                r#"{"type":"Array","value":[{"type":"UFix64","value":"500.00000000"},{"type":"UFix64","value":"1000.00000000"}]}"#,
+            ]
+        );
+        assert!(
+            PublicKeyArgs::array_arguments(&[PublicKeyArgs::new(vec![]).with_weight(-1.0)])
+                .is_err()
+        );
     }
-    /// Argument from dictionary `Vec<(String, String)>`
-    pub fn dictionary(values: Vec<(String, String)>) -> Argument<Vec<Value>> {
-        Argument {
-            r#type: "Dictionary",
-            value: values
-                .into_iter()
-                .map(|(x, y)| json!({"Key":x, "Value":y}))
-                .collect(),
-        }
+
+    #[test]
+    fn encoded_account_keys_pass_through() {
+        let raw = "ab".repeat(64);
+        let encoded = format!("f847b840{}02038203e8", raw);
+        assert_eq!(encode_account_key(&raw), encoded);
+        assert_eq!(encode_account_key(&format!("0x{}", raw)), encoded);
+        assert_eq!(encode_account_key(&encoded), encoded);
+        // a weight 500, SHA2_256 key from the flow-cli
+        let weighted = format!("f847b840{}02018201f4", raw);
+        assert_eq!(encode_account_key(&weighted), weighted);
     }
-    // process and encode bytes argument
-    pub fn encode_arr(&self) -> Vec<u8> {
-        to_vec(&json!(self)).unwrap()
+
+    #[cfg(feature = "p256-upstream")]
+    #[test]
+    fn upstream_backend_round_trip() {
+        use p256_upstream::ecdsa::signature::{DigestVerifier, Signature as _};
+        let signing_key = SigningKey::random(&mut OsRng);
+        let verifying_key = signing_key.verifying_key();
+        let public_key = hex::encode(&verifying_key.to_encoded_point(false).as_bytes()[1..]);
+        let private_key = PrivateKey::from(signing_key.to_bytes().to_vec());
+        let signature = sign(b"message", &private_key).unwrap();
+        assert_eq!(signature.len(), 64);
+        assert!(verify(b"message", &signature, &public_key).unwrap());
+        assert!(!verify(b"other message", &signature, &public_key).unwrap());
+        // the signature is over the SHA3_256 digest, as Flow expects
+        let signature = p256_upstream::ecdsa::Signature::from_bytes(&signature).unwrap();
+        assert!(verifying_key
+            .verify_digest(Sha3_256::new().chain(b"message"), &signature)
+            .is_ok());
     }
-}
-/// Boolean arguments
-impl Argument<bool> {
-    pub fn boolean(value: bool) -> Argument<bool> {
-        Argument {
-            r#type: "Bool",
-            value,
-        }
+
+    #[test]
+    fn account_proof_round_trip() {
+        let signing_key = SigningKey::random(&mut OsRng);
+        let public_key = hex::encode(
+            &signing_key
+                .verifying_key()
+                .to_encoded_point(false)
+                .as_bytes()[1..],
+        );
+        let signer = Sign::from_bytes("f8d6e0586b0a20c7", 0, signing_key.to_bytes().to_vec());
+        let nonce = "75f8587e5bd5f9dcc9909d0dae1f0ac5814458b2ae129620502cb936fde7120a";
+        let signature =
+            sign_account_proof("AWESOME-APP-ID", "0xf8d6e0586b0a20c7", nonce, &signer).unwrap();
+        assert!(verify_account_proof(
+            "AWESOME-APP-ID",
+            "f8d6e0586b0a20c7",
+            nonce,
+            &signature,
+            &public_key
+        )
+        .unwrap());
+        assert!(!verify_account_proof(
+            "OTHER-APP-ID",
+            "f8d6e0586b0a20c7",
+            nonce,
+            &signature,
+            &public_key
+        )
+        .unwrap());
     }
-}
-/// You can use this to avoid memory allocation when dealing only with str
-impl Argument<&str> {
-    pub fn str(value: &str) -> Argument<&str> {
-        Argument {
-            r#type: "String",
-            value,
-        }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn intercepted_service_gets_the_full_api() {
+        let mock = mock::MockAccessApi::new().with_account(Account {
+            address: hex::decode("f8d6e0586b0a20c7").unwrap(),
+            balance: 10,
+            ..Default::default()
+        });
+        let channel = Endpoint::from_shared(mock.serve().await.unwrap())
+            .unwrap()
+            .connect()
+            .await
+            .unwrap();
+        // tonic's interceptor signature returns the large `Status` as its error
+        #[allow(clippy::result_large_err)]
+        let authenticated = tonic::service::interceptor::InterceptedService::new(
+            channel,
+            |mut request: tonic::Request<()>| {
+                request
+                    .metadata_mut()
+                    .insert("authorization", "Bearer token".parse().unwrap());
+                Ok(request)
+            },
+        );
+        let mut connection = FlowConnection::from_service(authenticated);
+        let account = connection.get_account("f8d6e0586b0a20c7").await.unwrap();
+        assert_eq!(account.account_balance().unwrap(), 10);
     }
-    // process and encode bytes argument. Using this instead of `encode()` bypasses memory allocation as we don't have to worry about `String`s
-    pub fn encode_str(&self) -> Vec<u8> {
-        to_vec(&json!(self)).unwrap()
+
+    #[cfg(feature = "transport")]
+    #[tokio::test]
+    async fn emulator_connections_use_cadence_1() {
+        let connection = FlowConnection::emulator().unwrap();
+        assert_eq!(connection.cadence_version, CadenceVersion::Cadence1);
+        assert_eq!(
+            connection.templates.create_account,
+            CadenceVersion::Cadence1.templates().create_account
+        );
+        let connection = FlowConnection::new(EMULATOR_ACCESS_API).unwrap();
+        assert_eq!(connection.cadence_version, CadenceVersion::Legacy);
     }
-}
-/// You will use this for most argument types. Before implementing new types, be sure to read https://docs.onflow.org/cadence/json-cadence-spec
-impl Argument<String> {
-    /// Take a String and turn it into an argument
-    pub fn string(value: String) -> Argument<String> {
-        Argument {
-            r#type: "String",
-            value,
-        }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn poll_events_since_reads_in_chunks() {
+        let event = |index| Event {
+            r#type: "A.1.Test.Happened".to_owned(),
+            event_index: index,
+            ..Default::default()
+        };
+        let mock = mock::MockAccessApi::new()
+            .with_latest_block(Block {
+                height: 600,
+                ..Default::default()
+            })
+            .with_events(5, vec![event(0)])
+            .with_events(300, vec![event(1)])
+            .with_events(600, vec![event(2)]);
+        let mut connection = mock.connect().await.unwrap();
+        // 600 heights take three requests of at most MAX_EVENT_HEIGHT_RANGE
+        let (events, cursor) = connection
+            .poll_events_since("A.1.Test.Happened", EventCursor::at(1))
+            .await
+            .unwrap();
+        let indices: Vec<u32> = events.iter().map(|event| event.event_index).collect();
+        assert_eq!(indices, vec![0, 1, 2]);
+        assert_eq!(cursor, EventCursor { last_height: 600 });
+
+        let (events, unchanged) = connection
+            .poll_events_since("A.1.Test.Happened", cursor)
+            .await
+            .unwrap();
+        assert!(events.is_empty());
+        assert_eq!(unchanged, cursor);
+        assert_eq!(EventCursor::at(0), EventCursor { last_height: 0 });
     }
-    /// Take a positive f64 and turn it into an argument. Fixed point numbers are encoded as strings, so this will result in additional memory allocation when used.
-    pub fn ufix64(value: f64) -> Argument<String> {
-        assert!(value >= 0.0, "{}", true); // cannot have a negative ufix
-        Argument {
-            r#type: "UFix64",
-            value: value.to_string(),
-        }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn get_node_version_info_reports_the_spork() {
+        let info = NodeVersionInfo {
+            semver: "v0.33.1".to_owned(),
+            protocol_version: 32,
+            spork_root_block_height: 65264619,
+            ..Default::default()
+        };
+        let mock = mock::MockAccessApi::new().with_node_version_info(info.clone());
+        let mut connection = mock.connect().await.unwrap();
+        let response = connection.get_node_version_info().await.unwrap();
+        assert_eq!(response.info, Some(info));
+
+        let mut connection = mock::MockAccessApi::new().connect().await.unwrap();
+        let error = connection.get_node_version_info().await.unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<tonic::Status>().unwrap().code(),
+            tonic::Code::Unimplemented
+        );
     }
-    /// Take a f64 and turn it into an argument. Fixed point numbers are encoded as strings, so this will result in additional memory allocation when used.
-    pub fn fix64(value: f64) -> Argument<String> {
-        Argument {
-            r#type: "Fix64",
-            value: value.to_string(),
-        }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn script_query_runs_with_its_arguments() {
+        let mock = mock::MockAccessApi::new()
+            .with_script_result(br#"{"type":"String","value":"Hello"}"#.to_vec());
+        let mut connection = mock.connect().await.unwrap();
+        let query = ScriptQuery::new()
+            .source("pub fun main(name: String): String { return name }")
+            .arg(Argument::str("Hello"))
+            .at_height(1);
+        let greeting: String = query.execute_typed(&mut connection).await.unwrap();
+        assert_eq!(greeting, "Hello");
+        assert_eq!(query.arguments, vec![Argument::str("Hello").encode()]);
+        assert!(query
+            .clone()
+            .at_block("not hex")
+            .execute(&mut connection)
+            .await
+            .is_err());
+        assert!(ScriptQuery::new().execute(&mut connection).await.is_err());
     }
-    /// Take a u64 and turn it into an argument. Integers are encoded as strings, so this will result in additional memory allocation when used.
-    pub fn uint64(value: u64) -> Argument<String> {
-        Argument {
-            r#type: "UInt64",
-            value: value.to_string(),
-        }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn get_contract_source_reads_deployed_code() {
+        let mut contracts = std::collections::HashMap::new();
+        contracts.insert("Greeting".to_owned(), b"pub contract Greeting {}".to_vec());
+        let account = Account {
+            address: hex::decode("f8d6e0586b0a20c7").unwrap(),
+            contracts,
+            ..Default::default()
+        };
+        let mock = mock::MockAccessApi::new().with_account(account);
+        let mut connection = mock.connect().await.unwrap();
+        assert_eq!(
+            connection
+                .get_contract_source("f8d6e0586b0a20c7", "Greeting")
+                .await
+                .unwrap(),
+            "pub contract Greeting {}"
+        );
+        let error = connection
+            .get_contract_source("f8d6e0586b0a20c7", "Missing")
+            .await
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Contract Missing not found on account f8d6e0586b0a20c7"
+        );
     }
-    /// Take a i64 and turn it into an argument. Integers are encoded as strings, so this will result in additional memory allocation when used.
-    pub fn int64(value: i64) -> Argument<String> {
-        Argument {
-            r#type: "Int64",
-            value: value.to_string(),
-        }
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn get_accounts_returns_results_in_order() {
+        let account = Account {
+            address: hex::decode("f8d6e0586b0a20c7").unwrap(),
+            balance: 10,
+            ..Default::default()
+        };
+        let mock = mock::MockAccessApi::new().with_account(account);
+        let mut connection = mock.connect().await.unwrap();
+        let accounts = connection
+            .get_accounts(&["0x01cf0e2f2f715450", "f8d6e0586b0a20c7"])
+            .await;
+        assert_eq!(accounts.len(), 2);
+        let error = accounts[0].as_ref().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "account 0x01cf0e2f2f715450 failed: NotFound"
+        );
+        assert_eq!(
+            error.downcast_ref::<tonic::Status>().unwrap().code(),
+            tonic::Code::NotFound
+        );
+        assert!(matches!(
+            error.downcast_ref::<FlowError>(),
+            Some(FlowError::AccountNotFound)
+        ));
+        assert_eq!(accounts[1].as_ref().unwrap().account_balance().unwrap(), 10);
+
+        let missing = flow::AccountResponse { account: None };
+        assert!(matches!(
+            missing.account().unwrap_err().downcast_ref::<FlowError>(),
+            Some(FlowError::AccountNotFound)
+        ));
     }
-    /// Take a hex-encoded string and turn it into an argument.
-    pub fn address(value: String) -> Argument<String> {
-        Argument {
-            r#type: "Address",
-            value,
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn sequence_tracker_increments_locally() {
+        let account = Account {
+            address: hex::decode("f8d6e0586b0a20c7").unwrap(),
+            keys: vec![AccountKey {
+                id: 0,
+                sequence_number: 7,
+                weight: 1000,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mock = mock::MockAccessApi::new().with_account(account);
+        let mut connection = mock.connect().await.unwrap();
+        let tracker = SequenceTracker::new();
+        for expected in 7..10 {
+            assert_eq!(
+                tracker
+                    .next(&mut connection, "0xf8d6e0586b0a20c7", 0)
+                    .await
+                    .unwrap(),
+                expected
+            );
         }
+        tracker.reset("f8d6e0586b0a20c7", 0);
+        assert_eq!(
+            tracker
+                .next(&mut connection, "f8d6e0586b0a20c7", 0)
+                .await
+                .unwrap(),
+            7
+        );
+
+        // only sequence numbers the tracker handed out reset it
+        let proposal_key = |sequence_number| TransactionProposalKey {
+            address: hex::decode("f8d6e0586b0a20c7").unwrap(),
+            key_id: 0,
+            sequence_number,
+        };
+        tracker.rejected(&proposal_key(12));
+        assert_eq!(
+            tracker
+                .next(&mut connection, "f8d6e0586b0a20c7", 0)
+                .await
+                .unwrap(),
+            8
+        );
+        tracker.rejected(&proposal_key(8));
+        assert_eq!(
+            tracker
+                .next(&mut connection, "f8d6e0586b0a20c7", 0)
+                .await
+                .unwrap(),
+            7
+        );
     }
-    // process and encode bytes argument
-    pub fn encode(&self) -> Vec<u8> {
-        to_vec(&json!(self)).unwrap()
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn sequence_tracker_survives_transport_errors() {
+        let account = Account {
+            address: hex::decode("f8d6e0586b0a20c7").unwrap(),
+            keys: vec![AccountKey {
+                id: 0,
+                sequence_number: 7,
+                weight: 1000,
+                ..Default::default()
+            }],
+            ..Default::default()
+        };
+        let mock = mock::MockAccessApi::new()
+            .with_account(account)
+            .with_stalled_sends(1);
+        let tracker = SequenceTracker::new();
+        let mut connection = mock
+            .connect()
+            .await
+            .unwrap()
+            .with_timeout(Some(Duration::from_millis(200)))
+            .with_sequence_tracker(tracker.clone());
+        let payer = Sign::new(
+            "f8d6e0586b0a20c7",
+            0,
+            &hex::encode(SigningKey::random(&mut OsRng).to_bytes()),
+        );
+        let transaction = connection
+            .authorized_transaction(
+                b"transaction {}",
+                vec![],
+                &[],
+                "f8d6e0586b0a20c7",
+                std::slice::from_ref(&payer),
+                None,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            transaction.proposal_key.as_ref().unwrap().sequence_number,
+            7
+        );
+        assert!(connection.send_transaction(transaction).await.is_err());
+        // the timed out transaction may have been accepted, so its sequence number is not reused
+        assert_eq!(
+            tracker
+                .next(&mut connection, "f8d6e0586b0a20c7", 0)
+                .await
+                .unwrap(),
+            8
+        );
     }
-}
-/// Utility function. Provides the ability to
-fn padding(vec: &mut Vec<u8>, count: usize) {
-    let mut i: usize = count;
-    i -= vec.len();
-    while i > 0 {
-        vec.push(0);
-        i -= 1;
+
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn submit_once_resends_after_a_timeout_the_node_never_saw() {
+        let mock = mock::MockAccessApi::new().with_stalled_sends(1);
+        let mut connection = mock
+            .connect()
+            .await
+            .unwrap()
+            .with_timeout(Some(Duration::from_millis(200)));
+        let payer = Sign::new(
+            "f8d6e0586b0a20c7",
+            0,
+            &hex::encode(SigningKey::random(&mut OsRng).to_bytes()),
+        );
+        let transaction = connection
+            .authorized_transaction(
+                b"transaction {}",
+                vec![],
+                &[],
+                "f8d6e0586b0a20c7",
+                std::slice::from_ref(&payer),
+                Some(0),
+            )
+            .await
+            .unwrap();
+        let id = transaction_id(&transaction).unwrap();
+        let sent = connection.submit_once(transaction).await.unwrap();
+        assert_eq!(sent.id, id);
+        // the stalled send was dropped, so the transaction must have been sent again
+        assert_eq!(mock.sent_transactions().len(), 1);
     }
-}
-/// Construct a signature object. Pass this into the payload
-/// or envelope signatures when signing a transaction.
-pub struct Sign {
-    pub address: String,
-    pub key_id: u32,
-    pub private_key: String,
-}
-/// build_transaction will construct a `flow::Transaction` with the provided script and arguments.
-/// See the `Argument` struct for details on how to construct arguments.
-pub async fn build_transaction(
-    script: Vec<u8>,
-    arguments: Vec<Vec<u8>>,
-    reference_block_id: Vec<u8>,
-    gas_limit: u64,
-    proposer: TransactionProposalKey,
-    authorizers: Vec<String>,
-    payer: String,
-) -> Result<Transaction> {
-    Ok(Transaction {
-        script,
-        arguments,
-        reference_block_id,
-        gas_limit,
-        proposal_key: Some(proposer),
-        authorizers: authorizers
-            .iter()
-            .map(|x| hex::decode(x).unwrap())
-            .collect(),
-        payload_signatures: vec![],
-        envelope_signatures: vec![],
-        payer: hex::decode(payer).unwrap(),
-    })
-}
-/// Provides an envelope of the given transaction
-fn envelope_from_transaction(
-    transaction: Transaction,
-    payload_signatures: &[TransactionSignature],
-) -> Vec<u8> {
-    let proposal_key = transaction.proposal_key.unwrap();
-    let mut proposal_address = proposal_key.address;
-    padding(&mut proposal_address, 8);
-    let mut ref_block = transaction.reference_block_id;
-    padding(&mut ref_block, 32);
-    let mut stream = RlpStream::new_list(2);
 
-    stream.begin_list(9);
-    stream.append(&Bytes::from(transaction.script).to_vec());
-    stream.begin_list(transaction.arguments.len());
-    for (_i, arg) in transaction.arguments.into_iter().enumerate() {
-        stream.append(&Bytes::from(arg).to_vec());
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn send_with_updates_ends_when_sealed() {
+        use tokio_stream::StreamExt;
+        let mock = mock::MockAccessApi::new();
+        let mut connection = mock.connect().await.unwrap();
+        let payer = Sign::new(
+            "f8d6e0586b0a20c7",
+            0,
+            &hex::encode(SigningKey::random(&mut OsRng).to_bytes()),
+        );
+        let transaction = connection
+            .authorized_transaction(
+                b"transaction {}",
+                vec![],
+                &[],
+                "f8d6e0586b0a20c7",
+                std::slice::from_ref(&payer),
+                Some(0),
+            )
+            .await
+            .unwrap();
+        let updates: Vec<TransactionStatus> = connection
+            .send_with_updates(transaction, PollConfig::default())
+            .await
+            .unwrap()
+            .map(Result::unwrap)
+            .collect()
+            .await;
+        assert_eq!(updates, vec![TransactionStatus::Sealed]);
     }
 
-    stream.append(&Bytes::from(ref_block).to_vec());
-    stream.append(&transaction.gas_limit);
-    stream.append(&Bytes::from(proposal_address).to_vec());
-    stream.append(&proposal_key.key_id);
-    stream.append(&proposal_key.sequence_number);
-    stream.append(&Bytes::from(transaction.payer).to_vec());
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn hex_inputs_are_normalised_before_sending() {
+        let mock = mock::MockAccessApi::new();
+        let mut connection = mock.connect().await.unwrap();
+        let block_id = format!("0x{}", "ab".repeat(32));
+        connection
+            .get_block(Some(block_id), None, None)
+            .await
+            .unwrap();
+        assert!(connection
+            .get_block(Some("0xnot-hex".to_owned()), None, None)
+            .await
+            .is_err());
+        let from = Sign::new(
+            "f8d6e0586b0a20c7",
+            0,
+            &hex::encode(SigningKey::random(&mut OsRng).to_bytes()),
+        );
+        assert!(connection
+            .transfer_flow(1.0, "0xnot-hex", &from, PollConfig::default())
+            .await
+            .is_err());
+        assert!(mock.sent_transactions().is_empty());
+    }
 
-    stream.begin_list(transaction.authorizers.len());
-    for (_i, auth) in transaction.authorizers.into_iter().enumerate() {
-        stream.append(&Bytes::from(auth).to_vec());
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn get_transaction_status_stops_at_final_statuses() {
+        let result = |status: TransactionStatus, status_code| TransactionResultResponse {
+            status: status as i32,
+            status_code,
+            error_message: "panic".to_owned(),
+            ..Default::default()
+        };
+        let mock = mock::MockAccessApi::new()
+            .with_transaction_result(vec![1], result(TransactionStatus::Executed, 0))
+            .with_transaction_result(vec![2], result(TransactionStatus::Sealed, 1))
+            .with_transaction_result(vec![3], result(TransactionStatus::Expired, 0));
+        let mut connection = mock.connect().await.unwrap();
+        assert_eq!(
+            connection.get_transaction_status(vec![1]).await.unwrap(),
+            TransactionStatus::Executed
+        );
+        assert!(matches!(
+            connection
+                .get_transaction_status(vec![2])
+                .await
+                .unwrap_err()
+                .downcast_ref::<FlowError>(),
+            Some(FlowError::CadenceRuntime { status_code: 1, .. })
+        ));
+        assert!(connection.get_transaction_status(vec![3]).await.is_err());
+        // ids the node has never seen are unknown, rather than an error
+        assert_eq!(
+            connection.get_transaction_status(vec![4]).await.unwrap(),
+            TransactionStatus::Unknown
+        );
+        assert!(connection
+            .wait_for_seal(
+                vec![2],
+                PollConfig {
+                    interval: Duration::from_millis(1),
+                    max_attempts: 3,
+                }
+            )
+            .await
+            .is_err());
     }
 
-    stream.begin_list(payload_signatures.len());
-    for (i, sig) in payload_signatures.iter().enumerate() {
-        let signature = sig.signature.to_vec();
-        stream.begin_list(3);
-        stream.append(&(i as u32));
-        stream.append(&sig.key_id);
-        stream.append(&signature);
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn execute_transaction_collects_authorizer_signatures() {
+        let key = || hex::encode(SigningKey::random(&mut OsRng).to_bytes());
+        let payer = Sign::new("f8d6e0586b0a20c7", 0, &key());
+        let authorizer = Sign::new("01cf0e2f2f715450", 2, &key());
+        let mock = mock::MockAccessApi::new();
+        let mut connection = mock.connect().await.unwrap();
+        connection
+            .execute_transaction(
+                b"transaction { prepare(a: AuthAccount, b: AuthAccount) {} }",
+                vec![],
+                &[
+                    ("0x01cf0e2f2f715450", &[authorizer]),
+                    ("f8d6e0586b0a20c7", &[]),
+                ],
+                "f8d6e0586b0a20c7",
+                &[payer],
+                Some(0),
+            )
+            .await
+            .unwrap();
+        let sent = mock.sent_transactions();
+        assert_eq!(sent[0].authorizers.len(), 2);
+        assert_eq!(sent[0].payload_signatures.len(), 1);
+        assert_eq!(
+            sent[0].payload_signatures[0].address,
+            hex::decode("01cf0e2f2f715450").unwrap()
+        );
+        assert_eq!(sent[0].payload_signatures[0].key_id, 2);
+        assert_eq!(sent[0].envelope_signatures.len(), 1);
     }
 
-    stream.out().to_vec()
-}
-/// Provides a payload from a transaction
-fn payload_from_transaction(transaction: Transaction) -> Vec<u8> {
-    let proposal_key = transaction.proposal_key.unwrap();
-    let mut proposal_address = proposal_key.address;
-    padding(&mut proposal_address, 8);
-    let mut ref_block = transaction.reference_block_id;
-    padding(&mut ref_block, 32);
+    #[cfg(feature = "mock")]
+    #[tokio::test]
+    async fn mock_records_signed_transactions() {
+        let signing_key = SigningKey::random(&mut OsRng);
+        let public_key = hex::encode(
+            &signing_key
+                .verifying_key()
+                .to_encoded_point(false)
+                .as_bytes()[1..],
+        );
+        let account = Account {
+            address: hex::decode("f8d6e0586b0a20c7").unwrap(),
+            keys: vec![AccountKey {
+                id: 0,
+                public_key: hex::decode(&public_key).unwrap(),
+                sign_algo: 2,
+                hash_algo: 3,
+                weight: 1000,
+                sequence_number: 5,
+                revoked: false,
+            }],
+            ..Default::default()
+        };
+        let mock = mock::MockAccessApi::new().with_account(account);
+        let mut connection = mock.connect().await.unwrap();
+        assert_eq!(
+            connection
+                .check_signing_weight("f8d6e0586b0a20c7", &[0])
+                .await
+                .unwrap(),
+            1000
+        );
+        let signer = Sign::new("f8d6e0586b0a20c7", 0, &hex::encode(signing_key.to_bytes()));
+        connection
+            .remove_key(1, "f8d6e0586b0a20c7", vec![signer], None)
+            .await
+            .unwrap();
+        let sent = mock.sent_transactions();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].proposal_key.as_ref().unwrap().sequence_number, 5);
+        let envelope = [
+            &TRANSACTION_DOMAIN_TAG[..],
+            &envelope_from_transaction(&sent[0], &sent[0].payload_signatures).unwrap(),
+        ]
+        .concat();
+        assert!(verify(
+            &envelope,
+            &sent[0].envelope_signatures[0].signature,
+            &public_key
+        )
+        .unwrap());
 
-    let mut stream = RlpStream::new_list(9);
-    stream.append(&Bytes::from(transaction.script).to_vec());
-    stream.begin_list(transaction.arguments.len());
-    for (_i, arg) in transaction.arguments.into_iter().enumerate() {
-        stream.append(&Bytes::from(arg).to_vec());
+        // templates loaded at runtime replace the built-in ones
+        let remove_key =
+            String::from("transaction(keyIndex: Int) { prepare(signer: AuthAccount) {} }");
+        let templates = Templates {
+            remove_key: Cow::Owned(remove_key.clone()),
+            ..CadenceVersion::Legacy.templates()
+        };
+        let signer = Sign::new("f8d6e0586b0a20c7", 0, &hex::encode(signing_key.to_bytes()));
+        connection
+            .with_templates(templates)
+            .remove_key(1, "f8d6e0586b0a20c7", vec![signer], None)
+            .await
+            .unwrap();
+        assert_eq!(mock.sent_transactions()[1].script, remove_key.into_bytes());
     }
 
-    stream.append(&Bytes::from(ref_block).to_vec());
-    stream.append(&transaction.gas_limit);
-    stream.append(&Bytes::from(proposal_address).to_vec());
-    stream.append(&proposal_key.key_id);
-    stream.append(&proposal_key.sequence_number);
-    stream.append(&Bytes::from(transaction.payer).to_vec());
-
-    stream.begin_list(transaction.authorizers.len());
-    for (_i, auth) in transaction.authorizers.into_iter().enumerate() {
-        stream.append(&Bytes::from(auth).to_vec());
+    /// A transaction from the canonical Flow encoding vectors: proposer, payer and authorizer are all `0x01`
+    fn vector_transaction() -> Transaction {
+        Transaction {
+            script: b"transaction { execute { log(\"Hello, World!\") } }".to_vec(),
+            arguments: vec![],
+            reference_block_id: hex::decode(
+                "f0e4c2f76c58916ec258f246851bea091d14d4247a2fc3e18694461b1816e13b",
+            )
+            .unwrap(),
+            gas_limit: 42,
+            proposal_key: Some(TransactionProposalKey {
+                address: hex::decode("0000000000000001").unwrap(),
+                key_id: 4,
+                sequence_number: 10,
+            }),
+            payer: hex::decode("0000000000000001").unwrap(),
+            authorizers: vec![hex::decode("0000000000000001").unwrap()],
+            payload_signatures: vec![],
+            envelope_signatures: vec![],
+        }
     }
-    stream.out().to_vec()
-}
-/// Returns the provided message as bytes, signed by the private key.
-fn sign(message: Vec<u8>, private_key: String) -> Result<Vec<u8>> {
-    let secret_key = SecretKey::from_be_bytes(&hex::decode(private_key)?)?;
-    let sig_key = SigningKey::from(secret_key);
-    let signature = sig_key.sign(&message);
-    Ok(signature.as_bytes().to_vec())
-}
-/// Process key arguments. Intended for use with `create_account`
-pub fn process_keys_args(account_keys: Vec<String>) -> Argument<Vec<Value>> {
-    // do special processing for the keys, wrapping with algo, hash, and weight information:
-    // algo: ECDSA_P256
-    // hash: SHA3_256
-    // weight: 1000
-    Argument::array(
-        account_keys
-            .into_iter()
-            .map(|x| json!(Argument::string(format!("f847b840{}02038203e8", x))))
-            .collect::<Vec<Value>>(),
-    )
-}
-/// Sign the provided transaction.
-/// You will first need to `build_transaction`.
-pub async fn sign_transaction(
-    built_transaction: Transaction,
-    payload_signatures: Vec<&Sign>,
-    envelope_signatures: Vec<&Sign>,
-) -> Result<Option<Transaction>> {
-    let mut payload: Vec<TransactionSignature> = vec![];
-    let mut envelope: Vec<TransactionSignature> = vec![];
-    // for each of the payload private keys, sign the transaction
-    for signer in payload_signatures {
-        let encoded_payload: &[u8] = &payload_from_transaction(built_transaction.clone());
-        let mut domain_tag: Vec<u8> = b"FLOW-V0.0-transaction".to_vec();
-        // we need to pad 0s at the end of the domain_tag
-        padding(&mut domain_tag, 32);
-
-        let fully_encoded: Vec<u8> = [&domain_tag, encoded_payload].concat();
-        let mut addr = hex::decode(signer.address.clone()).unwrap();
-        padding(&mut addr, 8);
-
-        payload.push(TransactionSignature {
-            address: addr,
-            key_id: signer.key_id,
-            signature: sign(fully_encoded, signer.private_key.clone())?,
+
+    #[test]
+    fn transaction_encoding_vectors() {
+        let mut transaction = vector_transaction();
+        let payload = "f872b07472616e73616374696f6e207b2065786563757465207b206c6f67282248656c6c6f2c20576f726c64212229207d207dc0a0f0e4c2f76c58916ec258f246851bea091d14d4247a2fc3e18694461b1816e13b2a880000000000000001040a880000000000000001c9880000000000000001";
+        assert_eq!(
+            hex::encode(payload_from_transaction(&transaction).unwrap()),
+            payload
+        );
+
+        transaction.payload_signatures = vec![TransactionSignature {
+            address: hex::decode("0000000000000001").unwrap(),
+            key_id: 4,
+            signature: hex::decode(
+                "f7225388c1d69d57e6251c9fda50cbbf9e05131e5adb81e5aa0422402f048162",
+            )
+            .unwrap(),
+        }];
+        assert_eq!(
+            hex::encode(
+                envelope_from_transaction(&transaction, &transaction.payload_signatures).unwrap()
+            ),
+            format!(
+                "f899{}e4e38004a0f7225388c1d69d57e6251c9fda50cbbf9e05131e5adb81e5aa0422402f048162",
+                payload
+            )
+        );
+        assert_eq!(
+            hex::encode(transaction_id(&transaction).unwrap()),
+            "118d6462f1c4182501d56f04a0cd23cf685283194bb316dceeb215b353120b2b"
+        );
+
+        // a signature by a non-signer, or a missing proposal key, cannot be encoded
+        transaction.payload_signatures.push(TransactionSignature {
+            address: hex::decode("0000000000000009").unwrap(),
+            key_id: 0,
+            signature: vec![0xaa; 64],
         });
+        assert!(transaction_id(&transaction).is_err());
+        assert!(transaction_id(&Transaction::default()).is_err());
+        assert!(transaction_byte_size(&Transaction::default()).is_err());
     }
-    // for each of the envelope private keys, sign the transaction
-    for signer in envelope_signatures {
-        let encoded_payload: &[u8] =
-            &envelope_from_transaction(built_transaction.clone(), &payload);
-        let mut domain_tag: Vec<u8> = b"FLOW-V0.0-transaction".to_vec();
-        // we need to pad 0s at the end of the domain_tag
-        padding(&mut domain_tag, 32);
 
-        let fully_encoded: Vec<u8> = [&domain_tag, encoded_payload].concat();
-        let mut addr = hex::decode(signer.address.clone()).unwrap();
-        padding(&mut addr, 8);
+    #[test]
+    fn multi_authorizer_encoding_vectors() {
+        // payer 0x02, authorizers 0x01 and 0x03, so the signer indexes are 0x01 => 0, 0x02 => 1, 0x03 => 2
+        let mut transaction = vector_transaction();
+        transaction.arguments = vec![br#"{"type":"String","value":"foo"}"#.to_vec()];
+        transaction.payer = hex::decode("0000000000000002").unwrap();
+        transaction.authorizers = vec![
+            hex::decode("0000000000000001").unwrap(),
+            hex::decode("0000000000000003").unwrap(),
+        ];
+        let payload = "f89bb07472616e73616374696f6e207b2065786563757465207b206c6f67282248656c6c6f2c20576f726c64212229207d207de09f7b2274797065223a22537472696e67222c2276616c7565223a22666f6f227da0f0e4c2f76c58916ec258f246851bea091d14d4247a2fc3e18694461b1816e13b2a880000000000000001040a880000000000000002d2880000000000000001880000000000000003";
+        assert_eq!(
+            hex::encode(payload_from_transaction(&transaction).unwrap()),
+            payload
+        );
 
-        envelope.push(TransactionSignature {
-            address: addr,
-            key_id: signer.key_id,
-            signature: sign(fully_encoded, signer.private_key.clone())?,
-        });
+        transaction.payload_signatures = vec![
+            TransactionSignature {
+                address: hex::decode("0000000000000001").unwrap(),
+                key_id: 4,
+                signature: vec![0xaa; 64],
+            },
+            TransactionSignature {
+                address: hex::decode("0000000000000003").unwrap(),
+                key_id: 0,
+                signature: vec![0xbb; 64],
+            },
+        ];
+        transaction.envelope_signatures = vec![TransactionSignature {
+            address: hex::decode("0000000000000002").unwrap(),
+            key_id: 1,
+            signature: vec![0xcc; 64],
+        }];
+        assert_eq!(
+            hex::encode(
+                envelope_from_transaction(&transaction, &transaction.payload_signatures).unwrap()
+            ),
+            format!(
+                "f9012b{}f88cf8448004b840{}f8440280b840{}",
+                payload,
+                "aa".repeat(64),
+                "bb".repeat(64)
+            )
+        );
+        assert_eq!(
+            hex::encode(transaction_id(&transaction).unwrap()),
+            "dc96773a2cdaa6728cbf3b1f17438f17e11dc6078bcf9e0dbf93faf1687ca4b7"
+        );
     }
-    let signed_transaction = Some(Transaction {
-        script: built_transaction.script,
-        arguments: built_transaction.arguments,
-        reference_block_id: built_transaction.reference_block_id,
-        gas_limit: built_transaction.gas_limit,
-        proposal_key: built_transaction.proposal_key,
-        authorizers: built_transaction.authorizers,
-        payload_signatures: payload,
-        envelope_signatures: envelope,
-        payer: built_transaction.payer,
-    });
-    Ok(signed_transaction)
-}
 
-// ****************************************************
-// Testing
-// ****************************************************
+    #[test]
+    fn merged_signatures_are_routed_and_ordered() {
+        // payer 0x02, authorizers 0x03 and 0x01
+        let mut base = vector_transaction();
+        base.payer = hex::decode("0000000000000002").unwrap();
+        base.authorizers = vec![
+            hex::decode("0000000000000003").unwrap(),
+            hex::decode("0000000000000001").unwrap(),
+        ];
+        let signature = |address: &str, key_id: u32, byte: u8| TransactionSignature {
+            address: hex::decode(address).unwrap(),
+            key_id,
+            signature: vec![byte; 64],
+        };
+        base.payload_signatures = vec![signature("0000000000000003", 0, 0xaa)];
+
+        let merged = merge_signatures(
+            base,
+            vec![
+                signature("0000000000000002", 1, 0xcc),
+                signature("0000000000000001", 4, 0xbb),
+                signature("0000000000000003", 0, 0xdd),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            merged.payload_signatures,
+            vec![
+                signature("0000000000000001", 4, 0xbb),
+                signature("0000000000000003", 0, 0xaa),
+            ]
+        );
+        assert_eq!(
+            merged.envelope_signatures,
+            vec![signature("0000000000000002", 1, 0xcc)]
+        );
+        assert_eq!(signer_index(&merged, &merged.payer), Some(1));
+
+        // the envelope signatures cover the payload signatures, so the payload is closed once they exist
+        assert!(
+            merge_signatures(merged.clone(), vec![signature("0000000000000001", 5, 0xee)]).is_err()
+        );
+        // repeated signatures are still ignored
+        assert_eq!(
+            merge_signatures(merged.clone(), vec![signature("0000000000000001", 4, 0xee)]).unwrap(),
+            merged
+        );
+    }
 
-#[cfg(test)]
-mod tests {
     #[tokio::test]
-    async fn meaningful_test() {
-        println!("does not exist yet. :)")
+    async fn incremental_signing_keeps_existing_signatures() {
+        let authorizer_key = hex::encode(SigningKey::random(&mut OsRng).to_bytes());
+        let payer_signing_key = SigningKey::random(&mut OsRng);
+        let payer_key = hex::encode(payer_signing_key.to_bytes());
+        let mut transaction = vector_transaction();
+        transaction.payer = hex::decode("0000000000000002").unwrap();
+        let authorizer = Sign::new("0000000000000001", 4, &authorizer_key);
+        let payer = Sign::new("0000000000000002", 0, &payer_key);
+
+        // the authorizer signs the payload on one machine
+        let partial = sign_transaction(transaction, vec![&authorizer], vec![])
+            .await
+            .unwrap();
+        assert_eq!(partial.payload_signatures.len(), 1);
+        assert!(partial.envelope_signatures.is_empty());
+
+        // and the payer signs the envelope on another, without re-signing the payload
+        let signed = sign_transaction(partial.clone(), vec![&authorizer], vec![&payer])
+            .await
+            .unwrap();
+        assert_eq!(signed.payload_signatures, partial.payload_signatures);
+        assert_eq!(signed.envelope_signatures.len(), 1);
+        let envelope = [
+            &TRANSACTION_DOMAIN_TAG[..],
+            &envelope_from_transaction(&signed, &signed.payload_signatures).unwrap(),
+        ]
+        .concat();
+        let public_key = payer_signing_key.verifying_key().to_encoded_point(false);
+        assert!(verify(
+            &envelope,
+            &signed.envelope_signatures[0].signature,
+            &hex::encode(&public_key.as_bytes()[1..])
+        )
+        .unwrap());
+
+        // the payload can't be signed again after the envelope
+        let late = Sign::new("0000000000000001", 5, &authorizer_key);
+        assert!(sign_transaction(signed, vec![&late], vec![]).await.is_err());
+    }
+
+    #[test]
+    fn nested_composite_argument() {
+        let inner = Argument::struct_value(
+            "A.0000000000000001.Market.Listing",
+            vec![
+                ("price".to_owned(), json!(Argument::ufix64(1.5).unwrap())),
+                (
+                    "tags".to_owned(),
+                    json!(Argument::array(vec![json!(Argument::str("rare"))])),
+                ),
+            ],
+        );
+        let outer = Argument::composite(
+            CompositeKind::Resource,
+            "A.0000000000000001.Market.Collection",
+            vec![("listing".to_owned(), json!(inner))],
+        );
+        assert_eq!(
+            json!(outer),
+            json!({
+                "type": "Resource",
+                "value": {
+                    "id": "A.0000000000000001.Market.Collection",
+                    "fields": [{
+                        "name": "listing",
+                        "value": {
+                            "type": "Struct",
+                            "value": {
+                                "id": "A.0000000000000001.Market.Listing",
+                                "fields": [
+                                    {"name": "price", "value": {"type": "UFix64", "value": "1.50000000"}},
+                                    {"name": "tags", "value": {
+                                        "type": "Array",
+                                        "value": [{"type": "String", "value": "rare"}]
+                                    }}
+                                ]
+                            }
+                        }
+                    }]
+                }
+            })
+        );
+    }
+
+    #[test]
+    fn fixed_point_arguments() {
+        assert_eq!(Argument::ufix64(0.1).unwrap().value, "0.10000000");
+        assert_eq!(Argument::ufix64(42.0).unwrap().value, "42.00000000");
+        assert_eq!(Argument::fix64(-1.25).unwrap().value, "-1.25000000");
+        assert!(Argument::ufix64(0.123456789).is_err());
+        assert!(Argument::ufix64(-1.0).is_err());
+        assert!(Argument::fix64(f64::NAN).is_err());
+        assert!(Argument::fix64(1e12).is_err());
+    }
+
+    #[test]
+    fn usable_keys_skip_revoked_and_light_keys() {
+        let key = |id: u32, weight: u32, revoked: bool| AccountKey {
+            id,
+            weight,
+            revoked,
+            ..Default::default()
+        };
+        let account = Account {
+            keys: vec![key(0, 1000, true), key(1, 500, false), key(2, 1000, false)],
+            ..Default::default()
+        };
+        assert_eq!(first_usable_key(&account, 1000), Some(2));
+        assert_eq!(first_usable_key(&account, 500), Some(1));
+        assert_eq!(first_usable_key(&account, 1001), None);
+        assert!(key_sequence_number(&account, 0).is_err());
+        assert!(key_sequence_number(&account, 2).is_ok());
+    }
+
+    #[test]
+    fn fixed_point_values_are_exact() {
+        let max = br#"{"type":"UFix64","value":"184467440737.09551615"}"#;
+        let decoded = CadenceValue::decode(max).unwrap();
+        assert_eq!(decoded, CadenceValue::UFix64(u64::MAX));
+        assert_eq!(
+            decoded.fixed_point_string().unwrap(),
+            "184467440737.09551615"
+        );
+        let negative = CadenceValue::decode(br#"{"type":"Fix64","value":"-0.5"}"#).unwrap();
+        assert_eq!(negative, CadenceValue::Fix64(-50_000_000));
+        assert_eq!(negative.fixed_point_string().unwrap(), "-0.50000000");
+
+        assert_eq!(
+            Argument::ufix64_str("184467440737.09551615")
+                .unwrap()
+                .encode(),
+            max.to_vec()
+        );
+        assert_eq!(
+            Argument::fix64_str("-12.3").unwrap().encode(),
+            Argument::fix64(-12.3).unwrap().encode()
+        );
+        assert!(Argument::ufix64_str("184467440737.09551616").is_err());
+        assert!(Argument::ufix64_str("-1").is_err());
+        assert!(Argument::ufix64_str("1.123456789").is_err());
+        assert!(Argument::fix64_str("1e5").is_err());
+    }
+
+    #[test]
+    fn decode_script_results() {
+        let balance = br#"{"type":"UFix64","value":"12.50000000"}"#;
+        let balance: f64 = CadenceValue::decode(balance).unwrap().deserialize().unwrap();
+        assert_eq!(balance, 12.5);
+
+        let addresses = br#"{"type":"Array","value":[
+            {"type":"Address","value":"0x0000000000000001"},
+            {"type":"Address","value":"0x0000000000000002"}
+        ]}"#;
+        let addresses: Vec<String> = CadenceValue::decode(addresses)
+            .unwrap()
+            .deserialize()
+            .unwrap();
+        assert_eq!(addresses, vec!["0x0000000000000001", "0x0000000000000002"]);
     }
 }
@@ -0,0 +1,142 @@
+//! Integration tests against a running flow-emulator.
+//!
+//! These only run when `FLOW_EMULATOR` is set to the emulator's gRPC address, eg. `http://localhost:3569`.
+//! Otherwise each test returns immediately.
+//! The emulator must run Cadence 1.0, as current flow-emulator releases (v1.0.0 and later) do.
+//! Set `FLOW_SERVICE_KEY` to the hex-encoded private key of its service account if it is not the emulator's default.
+#![cfg(feature = "transport")]
+
+use flow_rust_sdk::*;
+
+const SERVICE_ACCOUNT: &str = EMULATOR_SERVICE_ACCOUNT;
+
+const CONTRACT_V1: &str = "
+access(all) contract Greeting {
+    access(all) fun hello(): String {
+        return \"Hello\"
+    }
+}";
+
+const CONTRACT_V2: &str = "
+access(all) contract Greeting {
+    access(all) fun hello(): String {
+        return \"Hello, World!\"
+    }
+}";
+
+/// Connects to the emulator and returns the service account's signer, if the emulator is configured
+async fn emulator() -> Option<(FlowConnection<tonic::transport::Channel>, Sign)> {
+    let address = std::env::var("FLOW_EMULATOR").ok()?;
+    let private_key =
+        std::env::var("FLOW_SERVICE_KEY").unwrap_or_else(|_| EMULATOR_SERVICE_KEY.to_owned());
+    let connection = FlowConnection::connect(&address)
+        .await
+        .expect("could not connect to the emulator")
+        .with_cadence_version(CadenceVersion::Cadence1);
+    let service = Sign::new(SERVICE_ACCOUNT, 0, &private_key);
+    Some((connection, service))
+}
+
+/// A fresh key pair: the hex-encoded private key and 64 byte public key
+fn key_pair() -> (String, String) {
+    let signing_key = SigningKey::random(&mut OsRng);
+    let public_key = signing_key.verifying_key().to_encoded_point(false);
+    (
+        hex::encode(signing_key.to_bytes()),
+        hex::encode(&public_key.as_bytes()[1..]),
+    )
+}
+
+#[tokio::test]
+async fn account_key_and_contract_lifecycle() {
+    let (mut connection, service) = match emulator().await {
+        Some(emulator) => emulator,
+        None => return,
+    };
+
+    // create an account
+    let (private_key, public_key) = key_pair();
+    let account = connection
+        .create_account(
+            &[PublicKeyArgs::new(hex::decode(&public_key).unwrap())],
+            SERVICE_ACCOUNT,
+            vec![service],
+            None,
+        )
+        .await
+        .unwrap();
+    assert_eq!(account.keys.len(), 1);
+    let address = hex::encode(&account.address);
+    let signer = || Sign::new(&address, 0, &private_key);
+
+    // add and revoke a key
+    let (_, second_public_key) = key_pair();
+    let sent = connection
+        .add_key(&second_public_key, &address, vec![signer()], None)
+        .await
+        .unwrap();
+    connection
+        .wait_for_seal(sent.id, PollConfig::default())
+        .await
+        .unwrap();
+    let account = connection
+        .get_account(&address)
+        .await
+        .unwrap()
+        .account
+        .unwrap();
+    assert_eq!(account.keys.len(), 2);
+
+    let sent = connection
+        .revoke_key(1, &address, vec![signer()], None)
+        .await
+        .unwrap();
+    connection
+        .wait_for_seal(sent.id, PollConfig::default())
+        .await
+        .unwrap();
+    let account = connection
+        .get_account(&address)
+        .await
+        .unwrap()
+        .account
+        .unwrap();
+    assert!(account.keys[1].revoked);
+
+    // deploy a contract, and run a script against it
+    let script = format!(
+        "import Greeting from 0x{}\naccess(all) fun main(): String {{ return Greeting.hello() }}",
+        address
+    );
+    let sent = connection
+        .add_contract_source("Greeting", CONTRACT_V1, &address, vec![signer()], None)
+        .await
+        .unwrap();
+    connection
+        .wait_for_seal(sent.id, PollConfig::default())
+        .await
+        .unwrap();
+    let greeting: String = connection
+        .execute_script_typed(script.as_bytes().to_vec(), vec![], None, None)
+        .await
+        .unwrap();
+    assert_eq!(greeting, "Hello");
+
+    // update the contract
+    connection
+        .update_contract_and_wait(
+            "Greeting",
+            &hex::encode(CONTRACT_V2),
+            &address,
+            vec![signer()],
+            None,
+            PollConfig::default(),
+        )
+        .await
+        .unwrap();
+    let greeting: String = connection
+        .execute_script_typed(script.as_bytes().to_vec(), vec![], None, None)
+        .await
+        .unwrap();
+    assert_eq!(greeting, "Hello, World!");
+}